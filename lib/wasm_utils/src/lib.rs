@@ -1,4 +1,8 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use serde::{ Serialize, Deserialize };
 use walrus::{ Module, ExportItem };
+use wasmparser::{ ExternalKind, Parser, Payload, TypeRef };
 
 pub fn list_exports(bytecode: &Vec<u8>) -> Result<Vec<String>, Box<dyn std::error::Error>> {
   // Parse the WASM module
@@ -20,10 +24,244 @@ pub fn list_exports(bytecode: &Vec<u8>) -> Result<Vec<String>, Box<dyn std::erro
   Ok(result)
 }
 
+/// Size of a WASM section in bytes
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WasmSection {
+  pub name: String,
+  pub size: usize,
+}
+
+/// Structural summary of a WASM module
+#[derive(Clone, Debug, Default)]
+pub struct WasmSummary {
+  pub size: usize,
+  pub sections: Vec<WasmSection>,
+  pub custom_sections: Vec<WasmSection>,
+  pub imports: Vec<String>,
+  pub exports: Vec<String>,
+  pub imported_functions: u32,
+  pub functions: u32,
+  pub data_segments: u32,
+  pub data_size: usize,
+}
+
+fn extern_kind(kind: ExternalKind) -> &'static str {
+  match kind {
+    ExternalKind::Func => "func",
+    ExternalKind::Table => "table",
+    ExternalKind::Memory => "memory",
+    ExternalKind::Global => "global",
+    ExternalKind::Tag => "tag",
+  }
+}
+
+fn type_ref_kind(ty: &TypeRef) -> &'static str {
+  match ty {
+    TypeRef::Func(_) => "func",
+    TypeRef::Table(_) => "table",
+    TypeRef::Memory(_) => "memory",
+    TypeRef::Global(_) => "global",
+    TypeRef::Tag(_) => "tag",
+  }
+}
+
+pub fn summarize(bytecode: &[u8]) -> Result<WasmSummary, Box<dyn Error>> {
+  let mut summary = WasmSummary { size: bytecode.len(), ..Default::default() };
+  for payload in Parser::new(0).parse_all(bytecode) {
+    let payload = payload?;
+    let name = match &payload {
+      Payload::TypeSection(_) => "type",
+      Payload::ImportSection(_) => "import",
+      Payload::FunctionSection(_) => "function",
+      Payload::TableSection(_) => "table",
+      Payload::MemorySection(_) => "memory",
+      Payload::TagSection(_) => "tag",
+      Payload::GlobalSection(_) => "global",
+      Payload::ExportSection(_) => "export",
+      Payload::StartSection { .. } => "start",
+      Payload::ElementSection(_) => "element",
+      Payload::DataCountSection { .. } => "data_count",
+      Payload::CodeSectionStart { .. } => "code",
+      Payload::DataSection(_) => "data",
+      _ => "",
+    };
+    if let (false, Some((_, range))) = (name.is_empty(), payload.as_section()) {
+      summary.sections.push(WasmSection { name: name.to_string(), size: range.len() });
+    }
+    match payload {
+      Payload::ImportSection(reader) => {
+        for import in reader {
+          let import = import?;
+          if let TypeRef::Func(_) = import.ty {
+            summary.imported_functions += 1;
+          }
+          summary.imports.push(format!("{}.{} ({})", import.module, import.name, type_ref_kind(&import.ty)));
+        }
+      }
+      Payload::FunctionSection(reader) => {
+        summary.functions = reader.count();
+      }
+      Payload::ExportSection(reader) => {
+        for export in reader {
+          let export = export?;
+          summary.exports.push(format!("{} ({})", export.name, extern_kind(export.kind)));
+        }
+      }
+      Payload::DataSection(reader) => {
+        for data in reader {
+          summary.data_segments += 1;
+          summary.data_size += data?.data.len();
+        }
+      }
+      Payload::CustomSection(reader) => {
+        summary.custom_sections.push(WasmSection { name: reader.name().to_string(), size: reader.data().len() });
+      }
+      _ => (),
+    }
+  }
+  Ok(summary)
+}
+
+/// A value of the deployed bytecode against the compiled output
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ValueDiff<T> {
+  pub deployed: T,
+  pub compiled: T,
+}
+
+/// Size of a section in the deployed bytecode and the compiled output, `None` if the section is absent
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SectionDiff {
+  pub name: String,
+  pub deployed: Option<usize>,
+  pub compiled: Option<usize>,
+}
+
+/// Entries present in only one of the modules
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ListDiff {
+  /// Present in the deployed bytecode but not in the compiled output
+  pub missing: Vec<String>,
+  /// Present in the compiled output but not in the deployed bytecode
+  pub extra: Vec<String>,
+}
+
+impl ListDiff {
+  fn new(deployed: &[String], compiled: &[String]) -> Self {
+    ListDiff {
+      missing: deployed.iter().filter(|i| !compiled.contains(i)).cloned().collect(),
+      extra: compiled.iter().filter(|i| !deployed.contains(i)).cloned().collect(),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.missing.is_empty() && self.extra.is_empty()
+  }
+}
+
+/// Structural differences between the deployed bytecode and the compiled output
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WasmDiff {
+  pub size: ValueDiff<usize>,
+  pub sections: Vec<SectionDiff>,
+  pub custom_sections: Vec<SectionDiff>,
+  pub imports: ListDiff,
+  pub exports: ListDiff,
+  pub imported_functions: ValueDiff<u32>,
+  pub functions: ValueDiff<u32>,
+  pub data_segments: ValueDiff<u32>,
+  pub data_size: ValueDiff<usize>,
+  /// Likely causes of the mismatch derived from the differences above
+  pub hints: Vec<String>,
+}
+
+fn section_diff(deployed: &[WasmSection], compiled: &[WasmSection]) -> Vec<SectionDiff> {
+  let mut names: Vec<&String> = Vec::new();
+  for s in deployed.iter().chain(compiled.iter()) {
+    if !names.contains(&&s.name) {
+      names.push(&s.name);
+    }
+  }
+  let size_of = |list: &[WasmSection], name: &str| -> Option<usize> {
+    let mut sizes = list.iter().filter(|s| s.name == name).peekable();
+    sizes.peek()?;
+    Some(sizes.map(|s| s.size).sum())
+  };
+  names
+    .into_iter()
+    .map(|name| SectionDiff { name: name.clone(), deployed: size_of(deployed, name), compiled: size_of(compiled, name) })
+    .collect()
+}
+
+/// Compare the structure of the deployed bytecode against the compiled output
+pub fn diff(deployed: &[u8], compiled: &[u8]) -> Result<WasmDiff, Box<dyn Error>> {
+  let d = summarize(deployed)?;
+  let c = summarize(compiled)?;
+  let sections = section_diff(&d.sections, &c.sections);
+  let custom_sections = section_diff(&d.custom_sections, &c.custom_sections);
+  let imports = ListDiff::new(&d.imports, &c.imports);
+  let exports = ListDiff::new(&d.exports, &c.exports);
+
+  let mut hints = Vec::new();
+  let only_deployed: BTreeSet<&str> = custom_sections
+    .iter()
+    .filter(|s| s.compiled.is_none())
+    .map(|s| s.name.as_str())
+    .collect();
+  let only_compiled: BTreeSet<&str> = custom_sections
+    .iter()
+    .filter(|s| s.deployed.is_none())
+    .map(|s| s.name.as_str())
+    .collect();
+  if !only_compiled.is_empty() {
+    hints.push(
+      format!(
+        "Compiled output contains custom sections absent from the deployed bytecode ({}), the deployed contract may have been stripped",
+        only_compiled.into_iter().collect::<Vec<_>>().join(", ")
+      )
+    );
+  }
+  if !only_deployed.is_empty() {
+    hints.push(
+      format!(
+        "Deployed bytecode contains custom sections absent from the compiled output ({}), the deployed contract may not have been stripped",
+        only_deployed.into_iter().collect::<Vec<_>>().join(", ")
+      )
+    );
+  }
+  if !exports.is_empty() {
+    hints.push(String::from("Exports differ, the contract was likely built from different source code"));
+  }
+  if !imports.is_empty() {
+    hints.push(String::from("Imports differ, the contract was likely built from different source code or SDK version"));
+  }
+  let code_differs = sections.iter().any(|s| s.deployed != s.compiled);
+  if exports.is_empty() && imports.is_empty() && code_differs {
+    hints.push(
+      String::from(
+        "Module interface is identical but section contents differ, the contract was likely built with a different compiler version or build options"
+      )
+    );
+  }
+
+  Ok(WasmDiff {
+    size: ValueDiff { deployed: d.size, compiled: c.size },
+    sections,
+    custom_sections,
+    imports,
+    exports,
+    imported_functions: ValueDiff { deployed: d.imported_functions, compiled: c.imported_functions },
+    functions: ValueDiff { deployed: d.functions, compiled: c.functions },
+    data_segments: ValueDiff { deployed: d.data_segments, compiled: c.data_segments },
+    data_size: ValueDiff { deployed: d.data_size, compiled: c.data_size },
+    hints,
+  })
+}
+
 #[cfg(test)]
 mod tests {
   use std::fs;
-  use super::list_exports;
+  use super::{ diff, list_exports, summarize };
 
   #[test]
   fn test_hello_world() {
@@ -32,4 +270,28 @@ mod tests {
     assert_eq!(exports.contains(&String::from("entrypoint")), true);
     assert_eq!(exports.contains(&String::from("hello_world")), true);
   }
+
+  #[test]
+  fn test_summarize() {
+    let file = fs::read("../ipfs_dag/test/hello-world.wasm").unwrap();
+    let summary = summarize(&file).expect("should summarize wasm");
+    assert_eq!(summary.size, file.len());
+    assert!(summary.functions > 0);
+    assert!(summary.sections.iter().any(|s| s.name == "code"));
+    assert!(summary.exports.contains(&String::from("hello_world (func)")));
+  }
+
+  #[test]
+  fn test_diff() {
+    let a = fs::read("../ipfs_dag/test/hello-world.wasm").unwrap();
+    let b = fs::read("../ipfs_dag/test/build.wasm").unwrap();
+    let same = diff(&a, &a).expect("should diff wasm");
+    assert!(same.hints.is_empty());
+    assert!(same.exports.is_empty() && same.imports.is_empty());
+    assert!(same.sections.iter().all(|s| s.deployed == s.compiled));
+    let different = diff(&a, &b).expect("should diff wasm");
+    assert_eq!(different.size.deployed, a.len());
+    assert_eq!(different.size.compiled, b.len());
+    assert!(!different.hints.is_empty());
+  }
 }
@@ -0,0 +1,66 @@
+use std::{ collections::HashMap, sync::{ Arc, Mutex } };
+use tokio::sync::broadcast;
+use crate::types::cv::{ CVBuildEvent, CVLogEntry };
+
+/// Number of events buffered per build, slower subscribers skip the oldest events
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// In-process fan-out of live build events to subscribers, keyed by contract code CID
+#[derive(Clone, Default)]
+pub struct BuildEvents {
+  channels: Arc<Mutex<HashMap<String, broadcast::Sender<CVBuildEvent>>>>,
+}
+
+impl BuildEvents {
+  /// Subscribe to the events of a build, which may not have started yet
+  pub fn subscribe(&self, code: &str) -> broadcast::Receiver<CVBuildEvent> {
+    let mut channels = self.channels.lock().expect("build events lock poisoned");
+    // drop channels of builds that never started and have no subscribers left
+    channels.retain(|_, tx| tx.receiver_count() > 0);
+    channels
+      .entry(code.to_string())
+      .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+      .subscribe()
+  }
+
+  /// Publish an event to the current subscribers of a build, if any
+  pub fn publish(&self, code: &str, event: CVBuildEvent) {
+    let channels = self.channels.lock().expect("build events lock poisoned");
+    if let Some(tx) = channels.get(code) {
+      let _ = tx.send(event);
+    }
+  }
+
+  /// Publish the final status of a build and end the streams of its subscribers
+  pub fn finish(&self, code: &str, status: &str) {
+    let mut channels = self.channels.lock().expect("build events lock poisoned");
+    if let Some(tx) = channels.remove(code) {
+      let _ = tx.send(CVBuildEvent::Status { status: status.to_string() });
+    }
+  }
+
+  pub fn state(&self, code: &str, state: &str) {
+    self.publish(code, CVBuildEvent::State { state: state.to_string() });
+  }
+
+  pub fn log(&self, code: &str, entry: &CVLogEntry) {
+    self.publish(code, CVBuildEvent::Log(entry.clone()));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn fan_out() {
+    let events = BuildEvents::default();
+    events.state("bafy", "cloning");
+    let mut rx = events.subscribe("bafy");
+    events.state("bafy", "compiling");
+    events.finish("bafy", "success");
+    assert!(matches!(rx.try_recv(), Ok(CVBuildEvent::State { state }) if state == "compiling"));
+    assert!(matches!(rx.try_recv(), Ok(CVBuildEvent::Status { status }) if status == "success"));
+    assert!(rx.try_recv().is_err());
+  }
+}
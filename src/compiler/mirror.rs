@@ -0,0 +1,107 @@
+use bson::doc;
+use futures_util::StreamExt;
+use git2::Oid;
+use log::{ error, info };
+use mongodb::options::FindOptions;
+use std::{ fs, path::Path };
+use crate::config::{ CompilerConf, GiteaConf };
+use crate::mongo::MongoDB;
+use crate::types::cv::{ CVContract, CVStatus };
+use super::git_host::{ clone_repo, parse_repo_url, CloneError, GitRef };
+use super::push_to_gitea;
+
+/// Maximum number of verified contracts mirrored per backfill run
+const BACKFILL_BATCH: i64 = 50;
+
+fn clone_error(e: CloneError) -> String {
+  match e {
+    CloneError::TooLarge => String::from("Repository is too large"),
+    CloneError::NotFound(e) => format!("Repository not found or not public: {}", e),
+    CloneError::Other(e) => format!("Failed to clone repository: {}", e),
+  }
+}
+
+/// Re-clone a verified contract at its verified commit into `work_dir` and push it to Gitea
+async fn mirror(
+  http_client: &reqwest::Client,
+  options: &CompilerConf,
+  gitea: &GiteaConf,
+  contract: &CVContract,
+  git_commit: &str,
+  work_dir: &Path
+) -> Result<String, String> {
+  let repo_url = contract.repo_url.clone().unwrap_or(format!("https://github.com/{}", contract.repo_name));
+  let repo = parse_repo_url(&repo_url, options.allowed_git_hosts.as_ref()).ok_or(format!("Invalid repository URL {}", repo_url))?;
+  let commit = Oid::from_str(git_commit).map_err(|e| e.to_string())?;
+  let max_bytes = options.max_repo_size.unwrap_or(102400) * 1024;
+  // the full history is fetched as Gitea does not accept shallow pushes
+  let _ = fs::remove_dir_all(work_dir);
+  let git_repo = match clone_repo(&repo.url, work_dir, &GitRef::Commit(commit), 0, max_bytes) {
+    Ok(r) => r,
+    Err(_) => {
+      // some hosts do not allow fetching commits by hash, fall back to the revision of the original request
+      let git_ref = GitRef::from_fields(&contract.repo_branch, contract.repo_tag.as_deref(), contract.repo_commit.as_deref())?;
+      let _ = fs::remove_dir_all(work_dir);
+      clone_repo(&repo.url, work_dir, &git_ref, 0, max_bytes).map_err(clone_error)?
+    }
+  };
+  git_repo.find_commit(commit).map_err(|e| format!("Verified commit {} is no longer available: {}", git_commit, e))?;
+  push_to_gitea(
+    http_client,
+    gitea,
+    &contract.code,
+    &contract.repo_name,
+    &contract.repo_branch,
+    git_commit,
+    &work_dir.to_string_lossy()
+  ).await
+}
+
+/// Mirror successfully verified contracts from git repositories that have no Gitea mirror yet, recording the outcome of
+/// each attempt. Contracts are skipped once they have used up the maximum mirror attempts.
+pub async fn backfill(db: &MongoDB, http_client: &reqwest::Client, options: &CompilerConf, gitea: &GiteaConf, work_dir: &str) {
+  let max_attempts = gitea.max_attempts.unwrap_or(5);
+  let filter =
+    doc! {
+    "status": CVStatus::Success.to_string(),
+    "gitea_url": null,
+    "git_commit": { "$ne": null },
+    "$or": [{ "gitea_attempts": { "$exists": false } }, { "gitea_attempts": { "$lt": max_attempts } }],
+  };
+  let opt = FindOptions::builder().sort(doc! { "verified_ts": 1 }).limit(BACKFILL_BATCH).build();
+  let mut cursor = match db.cv_contracts.find(filter).with_options(opt).await {
+    Ok(c) => c,
+    Err(e) => {
+      error!("Failed to query contracts to mirror: {}", e);
+      return;
+    }
+  };
+  let mut pending = Vec::new();
+  while let Some(contract) = cursor.next().await {
+    if let Ok(c) = contract {
+      pending.push(c);
+    }
+  }
+  if pending.is_empty() {
+    return;
+  }
+  info!("Mirroring {} verified contracts to Gitea", pending.len());
+  let work_dir = Path::new(work_dir);
+  for contract in pending {
+    let git_commit = contract.git_commit.clone().unwrap_or_default();
+    let update = match mirror(http_client, options, gitea, &contract, &git_commit, work_dir).await {
+      Ok(url) => {
+        info!("Mirrored contract {} to {}", contract.contract_id, url);
+        doc! { "$set": { "gitea_url": url }, "$unset": { "gitea_error": "" } }
+      }
+      Err(e) => {
+        error!("Failed to mirror contract {}: {}", contract.contract_id, e);
+        doc! { "$set": { "gitea_error": e }, "$inc": { "gitea_attempts": 1 } }
+      }
+    };
+    if let Err(e) = db.cv_contracts.update_one(doc! { "_id": &contract.code }, update).await {
+      error!("Failed to record mirror outcome of contract {}: {}", contract.contract_id, e);
+    }
+  }
+  let _ = fs::remove_dir_all(work_dir);
+}
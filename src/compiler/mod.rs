@@ -0,0 +1,1480 @@
+use bson::doc;
+use ipfs_dag::put_dag_raw;
+use mongodb::options::{ FindOneAndUpdateOptions, ReturnDocument };
+use tokio::sync::{ Mutex, Notify };
+use bollard::container::Config;
+use bollard::models::HostConfig;
+use chrono::Utc;
+use tokio::time::{ sleep, Duration };
+use git2::{ Cred, PushOptions, RemoteCallbacks, Repository };
+use wasm_utils::{ diff as wasm_diff, list_exports, WasmDiff };
+use std::{ collections::{ BTreeMap, HashMap }, error::Error, fs, io, path::Path, process::{ self, Command }, sync::{ Arc, Mutex as StdMutex } };
+use log::{ info, debug, error, warn };
+use crate::config::{ CompilerConf, GiteaConf, GoCompilerConf };
+use crate::helpers::{ archive::{ extract_archive, ArchiveKind, ArchiveLimits }, attestation };
+use crate::mongo::MongoDB;
+use crate::types::cv::{
+  CVAttemptLog,
+  CVAttemptSettings,
+  CVAttestation,
+  CVAttestationPayload,
+  CVBuildLog,
+  CVContract,
+  CVLogEntry,
+  CVPostProcessStep,
+  CVSbom,
+  CVSearchFileLines,
+  CVSearchPosting,
+  CVSourceFile,
+  CVSourceTree,
+  CVStatus,
+};
+use events::BuildEvents;
+use runner::{ BuildRunner, DockerRunner, PodmanRunner, DEPS_IMAGE };
+use toolchains::{
+  configured_tinygo_versions,
+  image_repo,
+  pinned_image,
+  tinygo_image,
+  tinygo_version_table,
+  toolchain_image,
+  TINYGO_IMAGE,
+};
+use git_host::{
+  checkout_commit,
+  clone_repo,
+  detect_license,
+  fetch_metadata,
+  parse_repo_url,
+  resolve_commit,
+  CloneError,
+  GitRef,
+  HostMetadata,
+  MetadataError,
+};
+
+pub mod events;
+pub mod git_host;
+pub mod mirror;
+pub mod post_process;
+pub mod runner;
+pub mod sbom;
+pub mod search;
+pub mod settings;
+pub mod snapshot;
+pub mod toolchains;
+pub mod upgrade;
+pub mod webhook;
+
+fn delete_if_exists(path: &str) -> Result<(), Box<dyn Error>> {
+  let p = Path::new(path);
+  if p.exists() {
+    if p.is_dir() {
+      fs::remove_dir_all(path)?;
+    } else {
+      fs::remove_file(path)?;
+    }
+  }
+  Ok(())
+}
+
+fn delete_dir_contents(read_dir_res: Result<fs::ReadDir, std::io::Error>) {
+  if let Ok(dir) = read_dir_res {
+    for entry in dir {
+      if let Ok(entry) = entry {
+        let path = entry.path();
+        if path.is_dir() {
+          fs::remove_dir_all(path).expect("Failed to remove a dir");
+        } else {
+          fs::remove_file(path).expect("Failed to remove a file");
+        }
+      }
+    }
+  }
+}
+
+fn create_dir_if_not_exists(path: String) -> io::Result<()> {
+  fs::create_dir(path).or_else(|e| {
+    if e.kind() == io::ErrorKind::AlreadyExists { Ok(()) } else { Err(e) }
+  })
+}
+
+fn chown(path: &String, uid: usize, gid: usize) {
+  let _ = Command::new("chown").arg("-R").arg(format!("{}:{}", uid, gid)).arg(path.clone()).status();
+}
+
+/// Per-worker copy of the compiler directories so that concurrent builds do not share working trees
+fn worker_go_options(go_options: &GoCompilerConf, worker: usize) -> GoCompilerConf {
+  let suffix = |dir: &String| format!("{}/worker-{}", dir.trim_end_matches('/'), worker);
+  let deps_dir = go_options.deps_dir.clone().unwrap_or(format!("{}-deps", go_options.src_dir.trim_end_matches('/')));
+  let deps_host_dir = go_options.deps_host_dir
+    .clone()
+    .or(go_options.src_host_dir.as_ref().map(|d| format!("{}-deps", d.trim_end_matches('/'))));
+  GoCompilerConf {
+    src_dir: suffix(&go_options.src_dir),
+    src_host_dir: go_options.src_host_dir.as_ref().map(suffix),
+    output_dir: suffix(&go_options.output_dir),
+    output_host_dir: go_options.output_host_dir.as_ref().map(suffix),
+    deps_dir: Some(suffix(&deps_dir)),
+    deps_host_dir: deps_host_dir.as_ref().map(suffix),
+    ..go_options.clone()
+  }
+}
+
+fn git_push_to_gitea(
+  gitea: &GiteaConf,
+  cid: &str,
+  branch: &str,
+  commit: &str,
+  src_dir: &str,
+) -> Result<String, String> {
+  let base = gitea.url.trim_end_matches('/');
+  let repo = Repository::open(src_dir).map_err(|e| format!("open {}: {}", src_dir, e))?;
+  let commit_oid = repo
+    .revparse_single(commit)
+    .map_err(|e| format!("revparse {}: {}", commit, e))?
+    .id();
+  let verified_ref = String::from("refs/heads/verified");
+  // verifications of a tag, commit or the default branch only have the verified branch
+  let mut mirror_refs = vec![verified_ref];
+  if !branch.is_empty() {
+    mirror_refs.push(format!("refs/heads/{}", branch));
+  }
+  for r in mirror_refs.iter() {
+    repo.reference(r, commit_oid, true, "mirror to gitea").map_err(|e| format!("create ref {}: {}", r, e))?;
+  }
+
+  let push_url = format!("{}/{}/{}.git", base, gitea.owner, cid);
+  let token = gitea.token.clone();
+  let mut callbacks = RemoteCallbacks::new();
+  callbacks.credentials(move |_url, _username, _allowed| {
+    Cred::userpass_plaintext("oauth2", &token)
+  });
+  let mut push_opts = PushOptions::new();
+  push_opts.remote_callbacks(callbacks);
+
+  let mut remote = repo
+    .remote_anonymous(&push_url)
+    .map_err(|e| format!("remote_anonymous {}: {}", push_url, e))?;
+  let refspecs: Vec<String> = mirror_refs
+    .iter()
+    .map(|r| format!("+{}:{}", r, r))
+    .collect();
+  let refspec_refs: Vec<&str> = refspecs.iter().map(String::as_str).collect();
+  remote
+    .push(&refspec_refs, Some(&mut push_opts))
+    .map_err(|e| format!("push to {}: {}", push_url, e))?;
+
+  Ok(format!("{}/{}/{}", base, gitea.owner, cid))
+}
+
+async fn push_to_gitea(
+  http_client: &reqwest::Client,
+  gitea: &GiteaConf,
+  cid: &str,
+  repo_name: &str,
+  branch: &str,
+  commit: &str,
+  src_dir: &str,
+) -> Result<String, String> {
+  let base = gitea.url.trim_end_matches('/');
+  let create_endpoint = if gitea.is_org.unwrap_or(false) {
+    format!("{}/api/v1/orgs/{}/repos", base, gitea.owner)
+  } else {
+    format!("{}/api/v1/user/repos", base)
+  };
+  let body = serde_json::json!({
+    "name": cid,
+    "description": format!("Verified contract {}@{}", repo_name, commit),
+    "auto_init": false,
+    "private": false,
+  });
+  let resp = http_client
+    .post(&create_endpoint)
+    .header("Authorization", format!("token {}", gitea.token))
+    .json(&body)
+    .send().await
+    .map_err(|e| format!("gitea create request failed: {}", e))?;
+  let status = resp.status();
+  if !status.is_success() && status.as_u16() != 409 {
+    let text = resp.text().await.unwrap_or_default();
+    return Err(format!("gitea create repo failed: {} {}", status, text));
+  }
+
+  git_push_to_gitea(gitea, cid, branch, commit, src_dir)
+}
+
+/// Interval in seconds between checks for verifications and webhook deliveries that are due for retry
+const RETRY_POLL_INTERVAL: u64 = 30;
+
+/// Delay in seconds before the next attempt, doubling after every attempt
+fn retry_backoff(base: u64, attempts: i32) -> u64 {
+  base.saturating_mul(1 << (attempts - 1).clamp(0, 10))
+}
+
+/// Rust dependency fetch script, run as `sh -c <script>`
+const RUST_FETCH_SCRIPT: &str =
+  r#"set -e
+rustup target add wasm32-unknown-unknown
+cargo fetch --locked --target wasm32-unknown-unknown"#;
+
+/// Rust build script, run as `sh -c <script> sh <profile> <features>`
+const RUST_BUILD_SCRIPT: &str =
+  r#"set -e
+if [ -n "$2" ]; then
+  cargo build --offline --locked --target wasm32-unknown-unknown --profile "$1" --features "$2"
+else
+  cargo build --offline --locked --target wasm32-unknown-unknown --profile "$1"
+fi
+case "$1" in release) d=release ;; dev) d=debug ;; *) d="$1" ;; esac
+cp "$CARGO_TARGET_DIR"/wasm32-unknown-unknown/"$d"/*.wasm /out/build.wasm"#;
+
+/// AssemblyScript dependency fetch script, run as `sh -c <script> sh <asc version>`
+const ASC_FETCH_SCRIPT: &str =
+  r#"set -e
+if [ -f package-lock.json ]; then npm ci --ignore-scripts; else npm install --ignore-scripts; fi
+npm install --no-save --ignore-scripts assemblyscript@"$1""#;
+
+/// AssemblyScript build script, run as `sh -c <script> sh <entry> [asc options...]`
+const ASC_BUILD_SCRIPT: &str =
+  r#"set -e
+e="$1"
+shift
+npx --no-install asc "$e" --outFile /out/build.wasm "$@""#;
+
+/// Toolchain containers run in two phases. Dependencies are fetched with network access first, then the
+/// contract is compiled from the resulting image without network access and with a read-only root filesystem.
+#[derive(Clone, Copy, PartialEq)]
+enum BuildPhase {
+  Fetch,
+  Compile,
+}
+
+/// Image digest of the TinyGo version recorded at request time, falling back to the configured version table for older requests
+fn tinygo_digest(contract: &CVContract, options: &CompilerConf) -> Result<String, String> {
+  if let Some(digest) = &contract.tinygo_img_digest {
+    return Ok(digest.clone());
+  }
+  let tinygo_version = contract.tinygo_version.clone().ok_or(String::from("Missing TinyGo version"))?;
+  configured_tinygo_versions(Some(options))
+    .get(&tinygo_version)
+    .map(|v| v.img_digest.clone())
+    .ok_or(format!("Unsupported TinyGo version {}", tinygo_version))
+}
+
+/// Toolchain image of a Rust or AssemblyScript contract pinned by digest
+fn toolchain_container_image(contract: &CVContract) -> Result<String, String> {
+  let (image, digest) = toolchain_image(contract)?;
+  Ok(pinned_image(&image, &digest))
+}
+
+/// Container config of a build phase using the toolchain of the contract language. The compile phase outputs `/out/build.wasm`.
+fn toolchain_container_conf(
+  contract: &CVContract,
+  options: &CompilerConf,
+  go_options: &GoCompilerConf,
+  phase: BuildPhase
+) -> Result<Config<String>, String> {
+  let src_base = go_options.src_host_dir.clone().unwrap_or(go_options.src_dir.clone());
+  let out_bind = format!("{}:/out", go_options.output_host_dir.clone().unwrap_or(go_options.output_dir.clone()));
+  let deps_dir = go_options.deps_dir.clone().ok_or(String::from("Missing dependency directory"))?;
+  let deps_bind = format!("{}:/deps", go_options.deps_host_dir.clone().unwrap_or(deps_dir));
+  let host_config = |src_bind: String| {
+    let mut host_config = HostConfig {
+      binds: Some(vec![src_bind, deps_bind.clone()]),
+      memory: Some(go_options.memory.unwrap_or(2147483648)),
+      nano_cpus: Some((go_options.cpus.unwrap_or(2.0) * 1e9) as i64),
+      pids_limit: Some(go_options.pids_limit.unwrap_or(512)),
+      ..Default::default()
+    };
+    match phase {
+      BuildPhase::Fetch => {
+        host_config.network_mode = go_options.fetch_network.clone();
+      }
+      BuildPhase::Compile => {
+        host_config.binds.as_mut().unwrap().push(out_bind.clone());
+        host_config.network_mode = Some(String::from("none"));
+        host_config.readonly_rootfs = Some(true);
+        host_config.tmpfs = Some(HashMap::from([(String::from("/tmp"), String::from("rw,exec,size=1g"))]));
+      }
+    }
+    host_config
+  };
+  let timed = |cmd: Vec<String>| {
+    let mut timed_cmd = vec![String::from("timeout"), format!("{}", go_options.timeout)];
+    timed_cmd.extend(cmd);
+    timed_cmd
+  };
+  let work_dir = match &contract.contract_dir {
+    Some(d) => format!("/src/{}", d),
+    None => String::from("/src"),
+  };
+  match contract.lang.as_str() {
+    "go" => {
+      let digest = tinygo_digest(contract, options)?;
+      let src_bind_host = match &contract.go_mod_dir {
+        Some(d) => format!("{}/{}", src_base, d),
+        None => src_base,
+      };
+      let mut env = vec![
+        String::from("GOMODCACHE=/deps/gomod"),
+        String::from("GOFLAGS=-modcacherw"),
+        String::from("GOCACHE=/tmp/gocache"),
+        String::from("XDG_CACHE_HOME=/tmp/cache")
+      ];
+      let cmd = match phase {
+        BuildPhase::Fetch => {
+          if let Some(proxy) = &go_options.goproxy {
+            env.push(format!("GOPROXY={}", proxy));
+          }
+          vec![String::from("go"), String::from("mod"), String::from("download")]
+        }
+        BuildPhase::Compile => {
+          env.push(String::from("GOPROXY=off"));
+          vec![
+            format!("tinygo"),
+            format!("build"),
+            format!("-gc=custom"),
+            format!("-scheduler=none"),
+            format!("-panic=trap"),
+            format!("-no-debug"),
+            format!("-target=wasm-unknown"),
+            format!("-o=/out/build.wasm"),
+            format!("./{}", contract.contract_dir.clone().unwrap_or(String::from("contract")))
+          ]
+        }
+      };
+      Ok(Config {
+        image: Some(tinygo_image(&digest)),
+        host_config: Some(host_config(format!("{}:/home/tinygo", src_bind_host))),
+        env: Some(env),
+        cmd: Some(timed(cmd)),
+        ..Default::default()
+      })
+    }
+    "rust" => {
+      let rust_version = contract.rust_version.clone().ok_or(String::from("Missing Rust version"))?;
+      let image = toolchain_container_image(contract)?;
+      let cmd = match phase {
+        BuildPhase::Fetch => vec![String::from("sh"), String::from("-c"), RUST_FETCH_SCRIPT.to_string()],
+        BuildPhase::Compile =>
+          vec![
+            String::from("sh"),
+            String::from("-c"),
+            RUST_BUILD_SCRIPT.to_string(),
+            String::from("sh"),
+            contract.cargo_profile.clone().unwrap_or(String::from("release")),
+            contract.cargo_features.clone().unwrap_or_default().join(",")
+          ],
+      };
+      Ok(Config {
+        image: Some(image),
+        host_config: Some(host_config(format!("{}:/src", src_base))),
+        working_dir: Some(work_dir),
+        // the requested toolchain takes precedence over any rust-toolchain file of the repository
+        env: Some(
+          vec![
+            String::from("CARGO_HOME=/deps/cargo"),
+            String::from("CARGO_TARGET_DIR=/tmp/target"),
+            format!("RUSTUP_TOOLCHAIN={}", rust_version)
+          ]
+        ),
+        cmd: Some(timed(cmd)),
+        ..Default::default()
+      })
+    }
+    "assemblyscript" => {
+      let asc_version = contract.asc_version.clone().ok_or(String::from("Missing AssemblyScript compiler version"))?;
+      let image = toolchain_container_image(contract)?;
+      let cmd = match phase {
+        BuildPhase::Fetch =>
+          vec![String::from("sh"), String::from("-c"), ASC_FETCH_SCRIPT.to_string(), String::from("sh"), asc_version],
+        BuildPhase::Compile => {
+          let mut cmd = vec![
+            String::from("sh"),
+            String::from("-c"),
+            ASC_BUILD_SCRIPT.to_string(),
+            String::from("sh"),
+            contract.asc_entry.clone().unwrap_or(String::from("assembly/index.ts"))
+          ];
+          cmd.extend(contract.asc_options.clone().unwrap_or_default());
+          cmd
+        }
+      };
+      Ok(Config {
+        image: Some(image),
+        host_config: Some(host_config(format!("{}:/src", src_base))),
+        working_dir: Some(work_dir),
+        env: Some(vec![String::from("npm_config_cache=/deps/npm")]),
+        cmd: Some(timed(cmd)),
+        ..Default::default()
+      })
+    }
+    lang => Err(format!("Unsupported contract language {}", lang)),
+  }
+}
+
+#[derive(Default)]
+struct BuildLogState {
+  entries: Vec<CVLogEntry>,
+  size: usize,
+  truncated: bool,
+}
+
+/// Build log of a single verification attempt, capped at a maximum total message size
+#[derive(Clone)]
+pub struct BuildLog {
+  state: Arc<StdMutex<BuildLogState>>,
+  max_size: usize,
+  /// Live events of the build and the contract code CID they are published under
+  events: Option<(BuildEvents, String)>,
+}
+
+impl BuildLog {
+  pub fn new(max_size: usize) -> Self {
+    BuildLog { state: Arc::new(StdMutex::new(BuildLogState::default())), max_size, events: None }
+  }
+
+  /// Build log that also publishes its entries and state transitions as live events
+  pub fn with_events(max_size: usize, events: &BuildEvents, code: &str) -> Self {
+    BuildLog { events: Some((events.clone(), code.to_string())), ..BuildLog::new(max_size) }
+  }
+
+  /// Publish a build state transition
+  pub fn state(&self, state: &str) {
+    if let Some((events, code)) = &self.events {
+      events.state(code, state);
+    }
+  }
+
+  pub fn push(&self, stage: &str, stream: &str, msg: &str) {
+    let entry = CVLogEntry { stage: stage.to_string(), stream: stream.to_string(), msg: msg.to_string() };
+    // live subscribers receive every line even after the stored log is truncated
+    if let Some((events, code)) = &self.events {
+      events.log(code, &entry);
+    }
+    let mut state = self.state.lock().expect("build log lock poisoned");
+    if state.truncated {
+      return;
+    }
+    if state.size + msg.len() > self.max_size {
+      state.truncated = true;
+      return;
+    }
+    state.size += msg.len();
+    state.entries.push(entry);
+  }
+
+  pub fn info(&self, stage: &str, msg: &str) {
+    self.push(stage, "info", msg);
+  }
+
+  /// Logged entries and whether the log was truncated
+  pub fn entries(&self) -> (Vec<CVLogEntry>, bool) {
+    let state = self.state.lock().expect("build log lock poisoned");
+    (state.entries.clone(), state.truncated)
+  }
+}
+
+/// Reason a verification build did not produce an output
+pub enum BuildError {
+  /// The build cannot succeed with the submitted settings
+  Failed(String),
+  /// Temporary failure of an external service, the contract should be requeued
+  Transient(String),
+}
+
+/// Output of a successful verification build
+struct BuildArtifact {
+  git_commit: Option<String>,
+  license: Option<String>,
+  wasm: Vec<u8>,
+  /// Manifest of the source files stored before any build step ran, to be saved if the verification succeeds
+  source_files: Option<Vec<CVSourceFile>>,
+  /// Dependencies of the checked out Go module, to be recorded if the verification succeeds
+  sbom: Option<CVSbom>,
+}
+
+/// Compiler worker that processes one verification at a time within its own directories and container
+#[derive(Clone)]
+struct Worker {
+  id: usize,
+  db: MongoDB,
+  runner: Arc<dyn BuildRunner>,
+  http_client: reqwest::Client,
+  options: CompilerConf,
+  go_options: GoCompilerConf,
+  gitea: Option<GiteaConf>,
+  events: BuildEvents,
+  delivery_trigger: Arc<Notify>,
+  cont_name: String,
+}
+
+impl Worker {
+  /// Clone the repository and checkout the commit to be verified, returning the commit hash and license
+  async fn fetch_git_source(&self, contract: &CVContract, log: &BuildLog) -> Result<(String, Option<String>), BuildError> {
+    let src_dir = Path::new(&self.go_options.src_dir);
+    let repo_url = contract.repo_url.clone().unwrap_or(format!("https://github.com/{}", contract.repo_name));
+    let repo = parse_repo_url(&repo_url, self.options.allowed_git_hosts.as_ref()).ok_or(
+      BuildError::Failed(format!("Invalid repository URL {}", repo_url))
+    )?;
+    let max_repo_size = self.options.max_repo_size.unwrap_or(102400);
+    let metadata = match fetch_metadata(&self.http_client, &self.options, &repo).await {
+      Ok(m) => m,
+      Err(MetadataError::NotFound) => {
+        return Err(BuildError::Failed(format!("Repository {} does not exist", repo.url)));
+      }
+      Err(MetadataError::Other(e)) => {
+        warn!("Failed to fetch repository metadata from {}: {}", repo.host, e);
+        HostMetadata::default()
+      }
+    };
+    if let Some(size) = metadata.size_kb {
+      if size > max_repo_size {
+        return Err(BuildError::Failed(format!("Repository is too large, size is {}", size)));
+      }
+    }
+    let git_ref = GitRef::from_fields(&contract.repo_branch, contract.repo_tag.as_deref(), contract.repo_commit.as_deref()).map_err(
+      BuildError::Failed
+    )?;
+    // mirroring to Gitea requires the history leading up to the commit, otherwise only the commit itself is fetched
+    let depth = if self.gitea.is_some() { 0 } else { 1 };
+    log.state("cloning");
+    log.info("git", &format!("Fetching {} of {}", git_ref, repo.url));
+    let git_repo = clone_repo(&repo.url, src_dir, &git_ref, depth, max_repo_size * 1024).map_err(|e| {
+      match e {
+        CloneError::TooLarge => BuildError::Failed(format!("Repository is larger than {} KB", max_repo_size)),
+        CloneError::NotFound(e) => BuildError::Failed(format!("Repository not found or not public: {}", e)),
+        CloneError::Other(e) => BuildError::Transient(format!("Failed to clone repository: {}", e)),
+      }
+    })?;
+    let commit = resolve_commit(&git_repo, &git_ref).map_err(|e|
+      BuildError::Failed(format!("Failed to resolve {}: {}", git_ref, e))
+    )?;
+    if let Err(e) = checkout_commit(&git_repo, commit) {
+      return Err(BuildError::Failed(format!("Failed to checkout commit: {}", e)));
+    }
+    log.info("git", &format!("Checked out commit {} of {}", commit, git_ref));
+    let license = detect_license(src_dir).or(metadata.license);
+    Ok((commit.to_string(), license))
+  }
+
+  /// Extract the uploaded source archive into the source directory
+  fn extract_source_archive(&self, archive: &str, log: &BuildLog) -> Result<(), BuildError> {
+    let archive_dir = self.options.archive_dir.clone().ok_or(BuildError::Failed(String::from("Archive directory is not configured")))?;
+    let data = fs::read(Path::new(&archive_dir).join(archive)).map_err(|e| BuildError::Failed(format!("Failed to read source archive: {}", e)))?;
+    let kind = ArchiveKind::detect(&data).ok_or(BuildError::Failed(String::from("Unsupported archive format")))?;
+    log.state("extracting");
+    log.info("archive", &format!("Extracting source archive {}", archive));
+    extract_archive(&data, kind, ArchiveLimits::from_conf(&self.options), Path::new(&self.go_options.src_dir)).map_err(|e|
+      BuildError::Failed(format!("Failed to extract source archive: {}", e))
+    )
+  }
+
+  /// Resolve the build settings of a request that specified none from the manifest in the source root
+  async fn apply_manifest(&self, contract: &mut CVContract, log: &BuildLog) -> Result<(), BuildError> {
+    let manifest = settings::read_manifest(Path::new(&self.go_options.src_dir))
+      .map_err(BuildError::Failed)?
+      .ok_or(BuildError::Failed(format!("No build settings were specified and {} was not found", settings::MANIFEST_FILE)))?;
+    if let Some(lang) = &manifest.lang {
+      if lang != &contract.lang {
+        return Err(BuildError::Failed(format!("{} declares language {} but the contract language is {}", settings::MANIFEST_FILE, lang, contract.lang)));
+      }
+    }
+    if manifest.settings.is_empty() {
+      return Err(BuildError::Failed(format!("{} does not specify any build settings", settings::MANIFEST_FILE)));
+    }
+    let tinygo_versions = match contract.lang.as_str() {
+      "go" => tinygo_version_table(&self.db, Some(&self.options)).await.map_err(|e| BuildError::Transient(e.to_string()))?,
+      _ => HashMap::new(),
+    };
+    settings::apply_build_settings(contract, &manifest.settings, &tinygo_versions, Some(&self.options)).map_err(|e|
+      BuildError::Failed(format!("Invalid {}: {}", settings::MANIFEST_FILE, e))
+    )?;
+    log.info("manifest", &format!("Using build settings from {}", settings::MANIFEST_FILE));
+    Ok(())
+  }
+
+  /// Fetch the contract source, compile it and return the resulting bytecode
+  async fn build(&self, contract: &mut CVContract, log: &BuildLog) -> Result<BuildArtifact, BuildError> {
+    let go_options = &self.go_options;
+    let (git_commit, license) = match &contract.source_archive {
+      Some(archive) => {
+        self.extract_source_archive(archive, log)?;
+        (None, None)
+      }
+      None => {
+        let (commit, license) = self.fetch_git_source(contract, log).await?;
+        (Some(commit), license)
+      }
+    };
+    if contract.build_manifest {
+      self.apply_manifest(contract, log).await?;
+    }
+    match contract.lang.as_str() {
+      "go" => {
+        let digest = tinygo_digest(contract, &self.options).map_err(BuildError::Failed)?;
+        self.runner.pull_image(TINYGO_IMAGE, &digest, log).await?;
+      }
+      _ => {
+        let (image, digest) = toolchain_image(contract).map_err(BuildError::Failed)?;
+        self.runner.pull_image(image_repo(&image), &digest, log).await?;
+      }
+    }
+    if contract.lang == "rust" {
+      self.ignore_toolchain_files(contract, log);
+    }
+    let contract = &*contract;
+    // the build steps can write to the source tree, so its contents are stored before any of them runs
+    let source_files = match &self.options.snapshot_dir {
+      Some(store_dir) => {
+        let src_dir = Path::new(&go_options.src_dir);
+        let files = snapshot::list_files(src_dir).map_err(BuildError::Transient)?;
+        match snapshot::store_snapshot(src_dir, &files, store_dir) {
+          Ok(m) => Some(m),
+          Err(e) => {
+            error!("Failed to snapshot source tree: {}", e);
+            log.info("snapshot", &format!("Failed to snapshot source tree: {}", e));
+            None
+          }
+        }
+      }
+      None => None,
+    };
+    let sbom = match contract.lang.as_str() {
+      "go" => self.read_sbom(contract, log),
+      _ => None,
+    };
+    let deps_dir = go_options.deps_dir.clone().unwrap_or_default();
+    if self.options.fix_permissions.unwrap_or(false) {
+      chown(&go_options.src_dir, 1000, 1000);
+      chown(&deps_dir, 1000, 1000);
+    }
+    // Fetch dependencies with network access, then compile from the resulting image without it
+    let fetch_conf = toolchain_container_conf(contract, &self.options, go_options, BuildPhase::Fetch).map_err(
+      BuildError::Failed
+    )?;
+    let deps_image = format!("{}:worker-{}", DEPS_IMAGE, self.id);
+    log.state("fetching");
+    let status_code = self.runner.run_container(&self.cont_name, fetch_conf, "fetch", Some(&deps_image), log).await?;
+    log.info("fetch", &format!("Dependency fetch exited with status code {}", status_code));
+    if status_code != 0 {
+      return Err(BuildError::Failed(format!("Dependency fetch failed with exit code {}", status_code)));
+    }
+    let mut cont_conf = toolchain_container_conf(contract, &self.options, go_options, BuildPhase::Compile).map_err(
+      BuildError::Failed
+    )?;
+    cont_conf.image = Some(deps_image.clone());
+    log.state("compiling");
+    let status_code = self.runner.run_container(&self.cont_name, cont_conf, "compile", None, log).await;
+    self.runner.remove_image(&deps_image).await;
+    let status_code = status_code?;
+    info!("Compiler exited with status code: {}", status_code);
+    log.info("compile", &format!("Compiler exited with status code {}", status_code));
+    if status_code != 0 {
+      return Err(BuildError::Failed(format!("Compilation failed with exit code {}", status_code)));
+    }
+    if let Some(steps) = &contract.post_process {
+      log.state("postprocessing");
+      self.post_process(steps, log)?;
+    }
+    let mut output = fs::read(format!("{}/build.wasm", go_options.output_dir)).map_err(|_|
+      BuildError::Failed(String::from("build.wasm not found"))
+    )?;
+    if let Some(tool) = contract.strip_tool.clone() {
+      log.state("stripping");
+      if self.strip(&tool, log) {
+        // this should not fail
+        output = fs::read(format!("{}/build-striped.wasm", go_options.output_dir)).map_err(|_|
+          BuildError::Failed(String::from("build-striped.wasm not found"))
+        )?;
+      }
+    }
+    Ok(BuildArtifact { git_commit, license, wasm: output, source_files, sbom })
+  }
+
+  /// Report rust-toolchain files of the repository, which are overridden by the requested Rust version
+  fn ignore_toolchain_files(&self, contract: &CVContract, log: &BuildLog) {
+    let src_dir = Path::new(&self.go_options.src_dir);
+    let mut dirs = vec![src_dir.to_path_buf()];
+    if let Some(d) = &contract.contract_dir {
+      // rustup looks for toolchain files from the working directory up to the source root
+      let mut dir = src_dir.join(d);
+      while dir != src_dir && dir.starts_with(src_dir) {
+        dirs.push(dir.clone());
+        dir.pop();
+      }
+    }
+    for dir in dirs {
+      for name in ["rust-toolchain.toml", "rust-toolchain"] {
+        let path = dir.join(name);
+        if path.exists() {
+          let rel = path.strip_prefix(src_dir).unwrap_or(&path).display().to_string();
+          log.info("fetch", &format!("Ignoring {} in favour of Rust {}", rel, contract.rust_version.clone().unwrap_or_default()));
+        }
+      }
+    }
+  }
+
+  /// Parse the module graph of the checked out Go module before dependencies are fetched
+  fn read_sbom(&self, contract: &CVContract, log: &BuildLog) -> Option<CVSbom> {
+    let mod_dir = match &contract.go_mod_dir {
+      Some(d) => Path::new(&self.go_options.src_dir).join(d),
+      None => Path::new(&self.go_options.src_dir).to_path_buf(),
+    };
+    match sbom::read_go_module(&mod_dir) {
+      Ok(Some(module)) => {
+        log.info("sbom", &format!("Found {} module dependencies of {}", module.components.len(), module.module));
+        Some(CVSbom {
+          code: contract.code.clone(),
+          contract_id: contract.contract_id.clone(),
+          module: module.module,
+          go_version: module.go_version,
+          components: module.components,
+          created_ts: bson::DateTime::from_chrono(Utc::now()),
+        })
+      }
+      Ok(None) => None,
+      Err(e) => {
+        log.info("sbom", &format!("Failed to parse go.mod: {}", e));
+        None
+      }
+    }
+  }
+
+  /// Run the post-processing steps in order on the compiled output, replacing `build.wasm` with the result of each step
+  fn post_process(&self, steps: &[CVPostProcessStep], log: &BuildLog) -> Result<(), BuildError> {
+    let input = format!("{}/build.wasm", self.go_options.output_dir);
+    let output = format!("{}/build-post.wasm", self.go_options.output_dir);
+    for step in steps.iter() {
+      let tool_conf = self.options.post_process_tools
+        .as_ref()
+        .and_then(|t| t.get(&step.tool))
+        .ok_or(BuildError::Failed(format!("Post-processing tool {} is not available", step.tool)))?;
+      let version = post_process::installed_version(tool_conf).map_err(BuildError::Transient)?;
+      if version != step.version {
+        return Err(BuildError::Failed(format!("Installed {} version {} does not match pinned version {}", step.tool, version, step.version)));
+      }
+      log.info("postprocess", &format!("Running {} {} {}", step.tool, version, step.flags.join(" ")));
+      let out = post_process::run_step(tool_conf, step, &input, &output).map_err(|e|
+        BuildError::Transient(format!("Failed to run {}: {}", step.tool, e))
+      )?;
+      for line in String::from_utf8_lossy(&out.stdout).lines() {
+        log.push("postprocess", "stdout", line);
+      }
+      for line in String::from_utf8_lossy(&out.stderr).lines() {
+        log.push("postprocess", "stderr", line);
+      }
+      log.info("postprocess", &format!("{} exited with {}", step.tool, out.status));
+      if !out.status.success() {
+        return Err(BuildError::Failed(format!("Post-processing with {} failed with {}", step.tool, out.status)));
+      }
+      fs::rename(&output, &input).map_err(|e| BuildError::Failed(format!("Failed to replace build.wasm: {}", e)))?;
+    }
+    Ok(())
+  }
+
+  /// Strip the compiled output into `build-striped.wasm`, returning whether it succeeded
+  fn strip(&self, tool: &str, log: &BuildLog) -> bool {
+    let input = format!("{}/build.wasm", self.go_options.output_dir);
+    let output = format!("{}/build-striped.wasm", self.go_options.output_dir);
+    let result = match tool {
+      "wabt" => Command::new(self.options.wasm_strip.clone()).arg("-o").arg(output).arg(input).output(),
+      "wasm-tools" => Command::new(self.options.wasm_tools.clone()).arg("strip").arg("-o").arg(output).arg(input).output(),
+      _ => {
+        return false;
+      }
+    };
+    match result {
+      Ok(out) => {
+        for line in String::from_utf8_lossy(&out.stdout).lines() {
+          log.push("strip", "stdout", line);
+        }
+        for line in String::from_utf8_lossy(&out.stderr).lines() {
+          log.push("strip", "stderr", line);
+        }
+        log.info("strip", &format!("{} exited with {}", tool, out.status));
+        out.status.success()
+      }
+      Err(e) => {
+        log.info("strip", &format!("Failed to run {}: {}", tool, e));
+        false
+      }
+    }
+  }
+
+  /// Fetch the deployed bytecode from the IPFS gateway, ensuring that it hashes to the contract code CID
+  async fn fetch_deployed_code(&self, code: &str) -> Result<Vec<u8>, String> {
+    let gateway = self.options.ipfs_gateway.clone().ok_or(String::from("IPFS gateway is not configured"))?;
+    let resp = self.http_client
+      .get(format!("{}/ipfs/{}?format=raw", gateway.trim_end_matches('/'), code))
+      .header("Accept", "application/vnd.ipld.raw")
+      .send().await
+      .map_err(|e| e.to_string())?;
+    if !resp.status().is_success() {
+      return Err(format!("IPFS gateway returned status code {}", resp.status()));
+    }
+    let bytes = resp.bytes().await.map_err(|e| e.to_string())?.to_vec();
+    if put_dag_raw(bytes.as_slice()) != code {
+      return Err(String::from("IPFS gateway returned bytecode that does not match the contract code CID"));
+    }
+    Ok(bytes)
+  }
+
+  /// Structural diff of the deployed bytecode against the compiled output
+  async fn diff_bytecode(&self, code: &str, compiled: &[u8], log: &BuildLog) -> Option<WasmDiff> {
+    let deployed = match self.fetch_deployed_code(code).await {
+      Ok(d) => d,
+      Err(e) => {
+        log.info("diff", &format!("Failed to fetch deployed bytecode: {}", e));
+        return None;
+      }
+    };
+    match wasm_diff(&deployed, compiled) {
+      Ok(d) => {
+        for hint in d.hints.iter() {
+          log.info("diff", hint);
+        }
+        Some(d)
+      }
+      Err(e) => {
+        log.info("diff", &format!("Failed to diff bytecode: {}", e));
+        None
+      }
+    }
+  }
+
+  /// Compare the build output against the deployed bytecode and record the verification result
+  async fn complete(&self, contract: &CVContract, artifact: BuildArtifact, output_cid: &str, log: &BuildLog) {
+    log.state("hashing");
+    let cid_match = output_cid == contract.code;
+    info!("Contract bytecode match: {}", cid_match.to_string().to_ascii_uppercase());
+    if !cid_match {
+      log.state("diffing");
+      log.info("diff", &format!("Compiled output {} does not match deployed bytecode {}", output_cid, contract.code));
+      let bytecode_diff = self
+        .diff_bytecode(&contract.code, &artifact.wasm, log).await
+        .and_then(|d| bson::to_bson(&d).ok());
+      let _ = self.db.cv_contracts.update_one(
+        doc! { "_id": &contract.code },
+        doc! {
+          "$set": {
+            "status": CVStatus::NotMatch.to_string(),
+            "completed_ts": bson::DateTime::from_chrono(Utc::now()),
+            "bytecode_diff": bytecode_diff,
+          }
+        }
+      ).await;
+      return;
+    }
+    let exports = list_exports(&artifact.wasm)
+      .map(Some)
+      .unwrap_or(None);
+    let gitea_result = match (&self.gitea, &artifact.git_commit) {
+      (Some(g), Some(git_commit)) => {
+        log.state("mirroring");
+        Some(
+          push_to_gitea(
+            &self.http_client,
+            g,
+            &contract.code,
+            &contract.repo_name,
+            &contract.repo_branch,
+            git_commit,
+            &self.go_options.src_dir
+          ).await
+        )
+      }
+      _ => None,
+    };
+    let completed_ts = bson::DateTime::from_chrono(Utc::now());
+    let mut set_doc =
+      doc! {
+      "status": CVStatus::Success.to_string(),
+      "verified_ts": completed_ts,
+      "completed_ts": completed_ts,
+      "git_commit": artifact.git_commit.clone(),
+      "license": artifact.license,
+      "exports": exports,
+    };
+    match gitea_result {
+      Some(Ok(u)) => {
+        set_doc.insert("gitea_url", u);
+      }
+      Some(Err(e)) => {
+        // left for the mirror backfill job to retry
+        error!("Gitea push failed: {}", e);
+        set_doc.insert("gitea_error", e);
+        set_doc.insert("gitea_attempts", 1);
+      }
+      None => (),
+    }
+    if let (Some(store_dir), Some(manifest)) = (&self.options.snapshot_dir, artifact.source_files) {
+      log.state("snapshotting");
+      self.save_snapshot(contract, manifest, &artifact.wasm, store_dir, log).await;
+    }
+    if let Some(sbom) = artifact.sbom {
+      if let Err(e) = self.db.cv_sboms.replace_one(doc! { "_id": &contract.code }, sbom).upsert(true).await {
+        error!("Failed to save SBOM: {}", e);
+      }
+    }
+    let _ = self.db.cv_contracts.update_one(doc! { "_id": &contract.code }, doc! { "$set": set_doc }).await;
+    if let Some(key) = &self.options.attestation_key {
+      self.attest(contract, artifact.git_commit, completed_ts, key, log).await;
+    }
+  }
+
+  /// Sign and record the attestation of a successful verification
+  async fn attest(&self, contract: &CVContract, git_commit: Option<String>, verified_ts: bson::DateTime, key: &str, log: &BuildLog) {
+    let key = match attestation::signing_key(key) {
+      Ok(k) => k,
+      Err(e) => {
+        error!("Invalid attestation key: {}", e);
+        return;
+      }
+    };
+    let toolchain_image = match contract.lang.as_str() {
+      "go" => tinygo_digest(contract, &self.options).ok().map(|d| tinygo_image(&d)),
+      _ => toolchain_container_image(contract).ok(),
+    };
+    let statement = CVAttestationPayload {
+      version: 1,
+      code: contract.code.clone(),
+      contract_id: contract.contract_id.clone(),
+      lang: contract.lang.clone(),
+      repo_url: contract.repo_url.clone(),
+      git_commit,
+      source_archive: contract.source_archive.clone(),
+      tinygo_version: contract.tinygo_version.clone(),
+      go_version: contract.go_version.clone(),
+      llvm_version: contract.llvm_version.clone(),
+      rust_version: contract.rust_version.clone(),
+      cargo_profile: contract.cargo_profile.clone(),
+      asc_version: contract.asc_version.clone(),
+      node_version: contract.node_version.clone(),
+      toolchain_image,
+      post_process: contract.post_process.clone(),
+      strip_tool: contract.strip_tool.clone(),
+      verified_ts: verified_ts.to_chrono().to_rfc3339(),
+    };
+    let (payload, signature) = attestation::sign(&key, &statement);
+    let record = CVAttestation {
+      code: contract.code.clone(),
+      contract_id: contract.contract_id.clone(),
+      payload,
+      signature,
+      public_key: attestation::public_key(&key),
+      algorithm: String::from(attestation::ALGORITHM),
+    };
+    match self.db.cv_attestations.replace_one(doc! { "_id": &contract.code }, record).upsert(true).await {
+      Ok(_) => log.info("attest", "Signed verification attestation"),
+      Err(e) => error!("Failed to save attestation: {}", e),
+    }
+  }
+
+  /// Store the compiled output and record the manifest of the source tree that was stored before the build
+  async fn save_snapshot(
+    &self,
+    contract: &CVContract,
+    manifest: Vec<CVSourceFile>,
+    wasm: &[u8],
+    store_dir: &str,
+    log: &BuildLog
+  ) {
+    log.info("snapshot", &format!("Saved snapshot of {} source files", manifest.len()));
+    let wasm_sha256 = match snapshot::store_blob(wasm, store_dir) {
+      Ok(h) => Some(h),
+      Err(e) => {
+        error!("Failed to store compiled output: {}", e);
+        None
+      }
+    };
+    let index = search::index_tree(store_dir, &manifest);
+    let tree = CVSourceTree {
+      code: contract.code.clone(),
+      contract_id: contract.contract_id.clone(),
+      snapshot_ts: bson::DateTime::from_chrono(Utc::now()),
+      files: manifest,
+      wasm_sha256,
+    };
+    if let Err(e) = self.db.cv_sources.replace_one(doc! { "_id": &contract.code }, tree).upsert(true).await {
+      error!("Failed to save source tree: {}", e);
+      return;
+    }
+    self.save_index(contract, index, log).await;
+  }
+
+  /// Replace the search index postings of the verified source tree
+  async fn save_index(&self, contract: &CVContract, index: BTreeMap<String, Vec<CVSearchFileLines>>, log: &BuildLog) {
+    if let Err(e) = self.db.cv_search.delete_many(doc! { "code": &contract.code }).await {
+      error!("Failed to remove previous search index: {}", e);
+      return;
+    }
+    let token_count = index.len();
+    let postings: Vec<CVSearchPosting> = index
+      .into_iter()
+      .map(|(token, files)| CVSearchPosting { token, code: contract.code.clone(), contract_id: contract.contract_id.clone(), files })
+      .collect();
+    if postings.is_empty() {
+      return;
+    }
+    match self.db.cv_search.insert_many(postings).await {
+      Ok(_) => log.info("index", &format!("Indexed {} distinct tokens for source search", token_count)),
+      Err(e) => error!("Failed to save search index: {}", e),
+    }
+  }
+
+  async fn save_log(&self, contract: &CVContract, log: &BuildLog) {
+    let (entries, truncated) = log.entries();
+    let build_log = CVBuildLog {
+      code: contract.code.clone(),
+      contract_id: contract.contract_id.clone(),
+      request_ts: contract.request_ts,
+      truncated,
+      logs: entries,
+    };
+    if let Err(e) = self.db.cv_logs.replace_one(doc! { "_id": &contract.code }, build_log).upsert(true).await {
+      error!("Failed to save build log: {}", e);
+    }
+  }
+
+  /// Record the build settings resolved from the manifest, even if the build did not succeed
+  async fn save_manifest_settings(&self, contract: &CVContract) {
+    let settings = match bson::to_document(&CVAttemptSettings::from_contract(contract)) {
+      Ok(d) => d,
+      Err(e) => {
+        error!("Failed to serialize build settings: {}", e);
+        return;
+      }
+    };
+    if let Err(e) = self.db.cv_contracts.update_one(doc! { "_id": &contract.code }, doc! { "$set": settings }).await {
+      error!("Failed to save build settings from manifest: {}", e);
+    }
+  }
+
+  /// Update the attempt record of the current request with the outcome of the build and its log.
+  /// `resolved` is the git commit and output CID of the build if it produced an output.
+  async fn record_attempt(&self, contract: &CVContract, resolved: (Option<String>, Option<String>), log: &BuildLog) {
+    let attempt_id = match contract.attempt_id {
+      Some(id) => id,
+      None => {
+        return;
+      }
+    };
+    let (entries, truncated) = log.entries();
+    let attempt_log = CVAttemptLog { id: attempt_id, truncated, logs: entries };
+    let has_logs = match self.db.cv_attempt_logs.replace_one(doc! { "_id": attempt_id }, attempt_log).upsert(true).await {
+      Ok(_) => true,
+      Err(e) => {
+        error!("Failed to save attempt log: {}", e);
+        false
+      }
+    };
+    let (git_commit, output_cid) = resolved;
+    let update =
+      doc! {
+      "$set": {
+        "status": &contract.status,
+        "attempts": contract.attempts,
+        "git_commit": git_commit.or(contract.git_commit.clone()),
+        "output_cid": output_cid,
+        "last_error": &contract.last_error,
+        "completed_ts": contract.completed_ts,
+        "has_logs": has_logs,
+        "settings": bson::to_bson(&CVAttemptSettings::from_contract(contract)).unwrap_or(bson::Bson::Null),
+      }
+    };
+    if let Err(e) = self.db.cv_attempts.update_one(doc! { "_id": attempt_id }, update).await {
+      error!("Failed to update verification attempt: {}", e);
+    }
+  }
+
+  /// Schedule another attempt with exponential backoff after a transient error, or fail once attempts are exhausted
+  async fn retry_later(&self, contract: &CVContract, error: &str, log: &BuildLog) {
+    let max_attempts = self.options.max_attempts.unwrap_or(5);
+    let update = if contract.attempts >= max_attempts {
+      log.info("error", &format!("Giving up after {} attempts", contract.attempts));
+      doc! {
+        "$set": {
+          "status": CVStatus::Failed.to_string(),
+          "completed_ts": bson::DateTime::from_chrono(Utc::now()),
+          "last_error": error,
+        }
+      }
+    } else {
+      let backoff = retry_backoff(self.options.retry_backoff.unwrap_or(60), contract.attempts);
+      let next_attempt = Utc::now() + chrono::Duration::seconds(backoff as i64);
+      log.info("error", &format!("Attempt {} of {} failed, retrying in {} seconds", contract.attempts, max_attempts, backoff));
+      doc! {
+        "$set": {
+          "status": CVStatus::Retrying.to_string(),
+          "next_attempt_ts": bson::DateTime::from_chrono(next_attempt),
+          "last_error": error,
+        }
+      }
+    };
+    let _ = self.db.cv_contracts.update_one(doc! { "_id": &contract.code }, update).await;
+  }
+
+  /// Start from empty source and dependency directories
+  fn reset_dirs(&self) {
+    let _ = delete_if_exists(self.go_options.src_dir.as_str());
+    let _ = create_dir_if_not_exists(self.go_options.src_dir.clone());
+    if let Some(deps_dir) = &self.go_options.deps_dir {
+      let _ = delete_if_exists(deps_dir);
+      let _ = create_dir_if_not_exists(deps_dir.clone());
+    }
+  }
+
+  fn clean_dirs(&self) {
+    debug!("Deleting build artifacts");
+    let _ = delete_if_exists(self.go_options.src_dir.as_str());
+    delete_dir_contents(fs::read_dir(self.go_options.output_dir.clone()));
+  }
+
+  /// Claim and build the oldest queued dry run, returning whether there was one. Dry runs are not retried.
+  async fn run_dry_run(&self) -> bool {
+    let opt = FindOneAndUpdateOptions::builder()
+      .sort(doc! { "request_ts": 1 })
+      .return_document(ReturnDocument::After)
+      .build();
+    let now = bson::DateTime::from_chrono(Utc::now());
+    let dry_run = match
+      self.db.cv_dry_runs
+        .find_one_and_update(
+          doc! { "status": CVStatus::Queued.to_string() },
+          doc! { "$set": { "status": CVStatus::InProgress.to_string(), "started_ts": now } }
+        )
+        .with_options(opt).await
+    {
+      Ok(Some(d)) => d,
+      Ok(None) => {
+        return false;
+      }
+      Err(e) => {
+        error!("Failed to get next dry run in queue: {}", e);
+        return false;
+      }
+    };
+    info!("Worker {} compiling dry run {}", self.id, dry_run.id);
+    self.reset_dirs();
+    let log = BuildLog::new(self.options.max_log_size.unwrap_or(1048576));
+    let mut build = dry_run.build.clone();
+    let mut set_doc = match self.build(&mut build, &log).await {
+      Ok(artifact) => {
+        let output_cid = put_dag_raw(artifact.wasm.as_slice());
+        log.info("hash", &format!("Compiled output CID is {}", output_cid));
+        doc! {
+          "status": CVStatus::Success.to_string(),
+          "git_commit": artifact.git_commit,
+          "output_cid": output_cid,
+          "exports": list_exports(&artifact.wasm).ok(),
+        }
+      }
+      Err(BuildError::Failed(e)) | Err(BuildError::Transient(e)) => {
+        error!("{}", e);
+        log.info("error", &e);
+        doc! { "status": CVStatus::Failed.to_string(), "error": e }
+      }
+    };
+    let (entries, truncated) = log.entries();
+    set_doc.insert("completed_ts", bson::DateTime::from_chrono(Utc::now()));
+    if build.build_manifest {
+      set_doc.insert("build", bson::to_bson(&build).unwrap_or(bson::Bson::Null));
+    }
+    set_doc.insert("truncated", truncated);
+    set_doc.insert("logs", bson::to_bson(&entries).unwrap_or(bson::Bson::Array(Vec::new())));
+    if let Err(e) = self.db.cv_dry_runs.update_one(doc! { "_id": dry_run.id }, doc! { "$set": set_doc }).await {
+      error!("Failed to save dry run result: {}", e);
+    }
+    self.clean_dirs();
+    true
+  }
+
+  async fn run(&self, running: Arc<Mutex<bool>>) {
+    let mut r = running.lock().await;
+    *r = true;
+    loop {
+      // atomically claim the oldest queued or due retrying contract so that no other worker picks it up
+      let opt = FindOneAndUpdateOptions::builder()
+        .sort(doc! { "request_ts": 1 })
+        .return_document(ReturnDocument::After)
+        .build();
+      let now = bson::DateTime::from_chrono(Utc::now());
+      let next_contract = self.db.cv_contracts
+        .find_one_and_update(
+          doc! {
+            "$or": [
+              { "status": CVStatus::Queued.to_string() },
+              { "status": CVStatus::Retrying.to_string(), "next_attempt_ts": { "$lte": now } },
+            ]
+          },
+          doc! {
+            "$set": { "status": CVStatus::InProgress.to_string(), "started_ts": now },
+            "$inc": { "attempts": 1 },
+          }
+        )
+        .with_options(opt).await;
+      if next_contract.is_err() {
+        error!("Failed to get next contract in queue");
+        break;
+      }
+      let next_contract = next_contract.unwrap();
+      if next_contract.is_none() {
+        // dry runs are only built while no verification is waiting
+        if self.run_dry_run().await {
+          continue;
+        }
+        break;
+      }
+      let mut next_contract = next_contract.unwrap();
+      info!("Worker {} compiling contract {}", self.id, &next_contract.contract_id);
+      info!("Code: {}", &next_contract.code);
+      self.reset_dirs();
+      let log = BuildLog::with_events(self.options.max_log_size.unwrap_or(1048576), &self.events, &next_contract.code);
+      let mut resolved: (Option<String>, Option<String>) = (None, None);
+      let build_result = self.build(&mut next_contract, &log).await;
+      if next_contract.build_manifest {
+        self.save_manifest_settings(&next_contract).await;
+      }
+      match build_result {
+        Ok(artifact) => {
+          let output_cid = put_dag_raw(artifact.wasm.as_slice());
+          resolved = (artifact.git_commit.clone(), Some(output_cid.clone()));
+          self.complete(&next_contract, artifact, &output_cid, &log).await
+        }
+        Err(BuildError::Failed(e)) => {
+          error!("{}", e);
+          log.info("error", &e);
+          let _ = self.db.cv_contracts.update_one(
+            doc! { "_id": &next_contract.code },
+            doc! {
+              "$set": {
+                "status": CVStatus::Failed.to_string(),
+                "completed_ts": bson::DateTime::from_chrono(Utc::now()),
+                "last_error": &e,
+              }
+            }
+          ).await;
+        }
+        Err(BuildError::Transient(e)) => {
+          error!("{}", e);
+          log.info("error", &e);
+          self.retry_later(&next_contract, &e, &log).await;
+        }
+      }
+      self.save_log(&next_contract, &log).await;
+      if let Ok(Some(c)) = self.db.cv_contracts.find_one(doc! { "_id": &next_contract.code }).await {
+        self.events.finish(&c.code, &c.status);
+        self.record_attempt(&c, resolved, &log).await;
+      }
+      if webhook::dispatch(&self.db, &next_contract.code).await {
+        self.delivery_trigger.notify_one();
+      }
+      self.clean_dirs();
+    }
+    debug!("Closing compiler worker {}", self.id);
+    *r = false;
+  }
+}
+
+/// Contract compiler
+#[derive(Clone)]
+pub struct Compiler {
+  db: MongoDB,
+  workers: Vec<Arc<Mutex<bool>>>,
+  runner: Arc<dyn BuildRunner>,
+  http_client: reqwest::Client,
+  options: CompilerConf,
+  go_options: GoCompilerConf,
+  gitea: Option<GiteaConf>,
+  events: BuildEvents,
+  mirror_trigger: Arc<Notify>,
+  delivery_trigger: Arc<Notify>,
+}
+
+impl Compiler {
+  /// Compiler using the container runtime selected in the config
+  pub fn init(
+    db: &MongoDB,
+    http_client: &reqwest::Client,
+    go_options: &GoCompilerConf,
+    options: &CompilerConf,
+    gitea: Option<GiteaConf>,
+  ) -> Self {
+    let runner: Result<Arc<dyn BuildRunner>, String> = match options.runtime.as_deref().unwrap_or("docker") {
+      "docker" => DockerRunner::connect().map(|r| Arc::new(r) as Arc<dyn BuildRunner>),
+      "podman" => PodmanRunner::connect(options.podman_socket.as_deref()).map(|r| Arc::new(r) as Arc<dyn BuildRunner>),
+      runtime => Err(format!("Unsupported container runtime {}", runtime)),
+    };
+    let runner = match runner {
+      Ok(r) => r,
+      Err(e) => {
+        error!("Failed to connect to container runtime: {}", e);
+        process::exit(1)
+      }
+    };
+    Compiler::with_runner(db, http_client, go_options, options, gitea, runner)
+  }
+
+  /// Compiler that runs toolchain containers with the given runner
+  pub fn with_runner(
+    db: &MongoDB,
+    http_client: &reqwest::Client,
+    go_options: &GoCompilerConf,
+    options: &CompilerConf,
+    gitea: Option<GiteaConf>,
+    runner: Arc<dyn BuildRunner>,
+  ) -> Self {
+    let worker_count = options.workers.unwrap_or(1).max(1);
+    Compiler {
+      db: db.clone(),
+      workers: (0..worker_count).map(|_| Arc::new(Mutex::new(false))).collect(),
+      runner,
+      http_client: http_client.clone(),
+      options: options.clone(),
+      go_options: go_options.clone(),
+      gitea,
+      events: BuildEvents::default(),
+      mirror_trigger: Arc::new(Notify::new()),
+      delivery_trigger: Arc::new(Notify::new()),
+    }
+  }
+
+  /// Recover verifications left in progress by a previous process, then keep waking up workers for due retries.
+  /// Webhook deliveries are sent from their own loop as soon as a worker queues them or a retry is due. Verified
+  /// contracts without a Gitea mirror are mirrored periodically, and upgraded code of verified contracts is queued for
+  /// re-verification if enabled.
+  pub fn start(&self) {
+    let compiler = self.clone();
+    tokio::spawn(async move {
+      compiler.recover_stale().await;
+      loop {
+        compiler.notify();
+        sleep(Duration::from_secs(RETRY_POLL_INTERVAL)).await;
+      }
+    });
+    let compiler = self.clone();
+    tokio::spawn(async move {
+      let delivery_client = webhook::delivery_client();
+      loop {
+        webhook::send_due_deliveries(&compiler.db, &delivery_client, &compiler.options).await;
+        let interval = Duration::from_secs(RETRY_POLL_INTERVAL);
+        let _ = tokio::time::timeout(interval, compiler.delivery_trigger.notified()).await;
+      }
+    });
+    if let Some(gitea) = self.gitea.clone() {
+      let compiler = self.clone();
+      let work_dir = format!("{}/mirror", self.go_options.src_dir);
+      tokio::spawn(async move {
+        loop {
+          mirror::backfill(&compiler.db, &compiler.http_client, &compiler.options, &gitea, &work_dir).await;
+          let interval = Duration::from_secs(gitea.backfill_interval.unwrap_or(3600));
+          let _ = tokio::time::timeout(interval, compiler.mirror_trigger.notified()).await;
+        }
+      });
+    }
+    if let Some(interval) = self.options.upgrade_watch_interval {
+      let compiler = self.clone();
+      tokio::spawn(async move {
+        loop {
+          if upgrade::watch(&compiler.db, &compiler.options).await > 0 {
+            compiler.notify();
+          }
+          sleep(Duration::from_secs(interval.max(RETRY_POLL_INTERVAL))).await;
+        }
+      });
+    }
+  }
+
+  /// Run the Gitea mirror backfill job now, returning false if Gitea mirroring is not configured
+  pub fn trigger_mirror_backfill(&self) -> bool {
+    if self.gitea.is_none() {
+      return false;
+    }
+    self.mirror_trigger.notify_one();
+    true
+  }
+
+  /// No worker is running at startup, so any verification still in progress was interrupted and is retried immediately
+  async fn recover_stale(&self) {
+    let now = bson::DateTime::from_chrono(Utc::now());
+    match
+      self.db.cv_contracts.update_many(
+        doc! { "status": CVStatus::InProgress.to_string() },
+        doc! { "$set": { "status": CVStatus::Retrying.to_string(), "next_attempt_ts": now } }
+      ).await
+    {
+      Ok(r) if r.modified_count > 0 => info!("Recovered {} interrupted contract verifications", r.modified_count),
+      Ok(_) => (),
+      Err(e) => error!("Failed to recover interrupted contract verifications: {}", e),
+    }
+    if
+      let Err(e) = self.db.cv_dry_runs.update_many(
+        doc! { "status": CVStatus::InProgress.to_string() },
+        doc! { "$set": { "status": CVStatus::Queued.to_string() } }
+      ).await
+    {
+      error!("Failed to requeue interrupted dry runs: {}", e);
+    }
+  }
+
+  /// Live build events, published while verifications are in progress
+  pub fn events(&self) -> &BuildEvents {
+    &self.events
+  }
+
+  /// Wake up idle workers to process the verification queue
+  pub fn notify(&self) {
+    for worker in 0..self.workers.len() {
+      if let Ok(r) = self.workers[worker].try_lock() {
+        if !*r {
+          self.run(worker);
+        }
+      }
+    }
+  }
+
+  fn run(&self, worker: usize) {
+    let go_options = worker_go_options(&self.go_options, worker);
+    let mkdir = fs
+      ::create_dir_all(go_options.output_dir.clone())
+      .and_then(|_| fs::create_dir_all(go_options.src_dir.clone()))
+      .and_then(|_| fs::create_dir_all(go_options.deps_dir.clone().unwrap_or_default()));
+    if mkdir.is_err() {
+      error!("Failed to create worker {} directories", worker);
+      return;
+    }
+    let w = Worker {
+      id: worker,
+      db: self.db.clone(),
+      runner: Arc::clone(&self.runner),
+      http_client: self.http_client.clone(),
+      options: self.options.clone(),
+      go_options,
+      gitea: self.gitea.clone(),
+      events: self.events.clone(),
+      delivery_trigger: Arc::clone(&self.delivery_trigger),
+      cont_name: format!("cv-compiler-{}", worker),
+    };
+    let running = Arc::clone(&self.workers[worker]);
+    debug!("Spawning compiler worker {}", worker);
+    tokio::spawn(async move {
+      w.run(running).await;
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::retry_backoff;
+
+  #[test]
+  fn exponential_backoff() {
+    assert_eq!(retry_backoff(60, 1), 60);
+    assert_eq!(retry_backoff(60, 2), 120);
+    assert_eq!(retry_backoff(60, 4), 480);
+    assert_eq!(retry_backoff(60, 50), 60 * 1024);
+    assert_eq!(retry_backoff(u64::MAX, 3), u64::MAX);
+  }
+}
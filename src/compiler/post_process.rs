@@ -0,0 +1,115 @@
+use std::{ collections::HashMap, process::{ Command, Output } };
+use crate::config::PostProcessToolConf;
+use crate::types::cv::CVPostProcessStep;
+
+/// Maximum number of post-processing steps of a verification
+pub const MAX_STEPS: usize = 8;
+
+/// Maximum number of flags of a post-processing step
+pub const MAX_FLAGS: usize = 16;
+
+/// Binaryen wasm-opt flags that may be passed to a `wasm-opt` step
+const WASM_OPT_ALLOWED_FLAGS: [&str; 24] = [
+  "-O",
+  "-O0",
+  "-O1",
+  "-O2",
+  "-O3",
+  "-O4",
+  "-Os",
+  "-Oz",
+  "--converge",
+  "--dce",
+  "--vacuum",
+  "--strip-debug",
+  "--strip-dwarf",
+  "--strip-producers",
+  "--strip-target-features",
+  "--zero-filled-memory",
+  "--low-memory-unused",
+  "--enable-bulk-memory",
+  "--enable-sign-ext",
+  "--enable-mutable-globals",
+  "--enable-nontrapping-float-to-int",
+  "--enable-multivalue",
+  "--enable-reference-types",
+  "--disable-bulk-memory",
+];
+
+/// Allowlisted flags of a post-processing tool, or none if the tool is not supported
+pub fn allowed_flags(tool: &str) -> Option<&'static [&'static str]> {
+  match tool {
+    "wasm-opt" => Some(&WASM_OPT_ALLOWED_FLAGS),
+    _ => None,
+  }
+}
+
+/// Validates the requested post-processing steps against the allowlist, pinning each tool to its configured version
+pub fn pin_steps(
+  steps: &[(String, Vec<String>)],
+  tools: Option<&HashMap<String, PostProcessToolConf>>
+) -> Result<Vec<CVPostProcessStep>, String> {
+  if steps.len() > MAX_STEPS {
+    return Err(format!("At most {} steps are allowed", MAX_STEPS));
+  }
+  steps
+    .iter()
+    .map(|(tool, flags)| {
+      let allowed = allowed_flags(tool).ok_or(format!("Unsupported tool {}", tool))?;
+      let tool_conf = tools.and_then(|t| t.get(tool)).ok_or(format!("Tool {} is not available", tool))?;
+      if flags.len() > MAX_FLAGS {
+        return Err(format!("At most {} flags are allowed per step", MAX_FLAGS));
+      }
+      if let Some(flag) = flags.iter().find(|f| !allowed.contains(&f.as_str())) {
+        return Err(format!("Flag {} is not allowed for {}", flag, tool));
+      }
+      Ok(CVPostProcessStep { tool: tool.clone(), flags: flags.clone(), version: tool_conf.version.clone() })
+    })
+    .collect()
+}
+
+/// Version reported by `<tool> --version`, which is the token that follows the word `version`
+pub fn parse_version(output: &str) -> Option<String> {
+  let mut tokens = output.split_whitespace();
+  tokens.find(|t| *t == "version")?;
+  tokens.next().map(String::from)
+}
+
+/// Installed version of a post-processing tool
+pub fn installed_version(tool_conf: &PostProcessToolConf) -> Result<String, String> {
+  let out = Command::new(&tool_conf.path)
+    .arg("--version")
+    .output()
+    .map_err(|e| format!("Failed to run {}: {}", tool_conf.path, e))?;
+  parse_version(&String::from_utf8_lossy(&out.stdout)).ok_or(format!("Failed to detect version of {}", tool_conf.path))
+}
+
+/// Run a post-processing step on `input`, writing the result into `output`
+pub fn run_step(tool_conf: &PostProcessToolConf, step: &CVPostProcessStep, input: &str, output: &str) -> std::io::Result<Output> {
+  Command::new(&tool_conf.path).args(&step.flags).arg("-o").arg(output).arg(input).output()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn pin_allowlisted_steps() {
+    let tools = HashMap::from([
+      (String::from("wasm-opt"), PostProcessToolConf { path: String::from("wasm-opt"), version: String::from("123") }),
+    ]);
+    let steps = vec![(String::from("wasm-opt"), vec![String::from("-Oz"), String::from("--strip-debug")])];
+    let pinned = pin_steps(&steps, Some(&tools)).unwrap();
+    assert_eq!(pinned[0].version, "123");
+    assert_eq!(pinned[0].flags, vec!["-Oz", "--strip-debug"]);
+    assert!(pin_steps(&steps, None).is_err());
+    assert!(pin_steps(&[(String::from("wasm-opt"), vec![String::from("--output=/etc/passwd")])], Some(&tools)).is_err());
+    assert!(pin_steps(&[(String::from("wasm2js"), vec![])], Some(&tools)).is_err());
+  }
+
+  #[test]
+  fn version_parsing() {
+    assert_eq!(parse_version("wasm-opt version 123 (version_123)\n"), Some(String::from("123")));
+    assert_eq!(parse_version("wasm-opt"), None);
+  }
+}
@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use bollard::{ Docker, API_DEFAULT_VERSION };
+use bollard::container::{ Config, CreateContainerOptions, LogOutput, LogsOptions, RemoveContainerOptions, WaitContainerOptions };
+use bollard::image::{ CommitContainerOptions, CreateImageOptions, RemoveImageOptions };
+use bollard::models::{ ContainerWaitResponse, ImageInspect };
+use futures_util::StreamExt;
+use log::{ debug, error };
+use std::env;
+use super::{ BuildError, BuildLog };
+
+/// Repository of the images committed by the dependency fetch phase, which only exist locally
+pub const DEPS_IMAGE: &str = "magi-cv-deps";
+
+/// Container runtime that runs the toolchain images of verification builds
+#[async_trait]
+pub trait BuildRunner: Send + Sync {
+  /// Pull an image by digest unless already present, then verify that the local image carries the expected digest
+  async fn pull_image(&self, repo: &str, digest: &str, log: &BuildLog) -> Result<(), BuildError>;
+
+  /// Run a container named `name` to completion, following its output into the build log under the given stage.
+  /// If `commit_as` is specified, the container is committed into an image of that name when it exits successfully.
+  async fn run_container(
+    &self,
+    name: &str,
+    cont_conf: Config<String>,
+    stage: &str,
+    commit_as: Option<&str>,
+    log: &BuildLog
+  ) -> Result<i64, BuildError>;
+
+  /// Remove an image committed by `run_container`, ignoring errors
+  async fn remove_image(&self, image: &str);
+}
+
+/// Runner backed by the Docker Engine API
+#[derive(Clone)]
+pub struct DockerRunner {
+  docker: Docker,
+}
+
+impl DockerRunner {
+  /// Connect to the local Docker daemon
+  pub fn connect() -> Result<Self, String> {
+    Docker::connect_with_local_defaults()
+      .map(|docker| DockerRunner { docker })
+      .map_err(|e| e.to_string())
+  }
+}
+
+#[async_trait]
+impl BuildRunner for DockerRunner {
+  async fn pull_image(&self, repo: &str, digest: &str, log: &BuildLog) -> Result<(), BuildError> {
+    let image = format!("{}@{}", repo, digest);
+    let has_digest = |inspect: &ImageInspect| {
+      inspect.repo_digests
+        .as_ref()
+        .map(|d| d.iter().any(|d| d.ends_with(&format!("@{}", digest))))
+        .unwrap_or(false)
+    };
+    if let Ok(inspect) = self.docker.inspect_image(&image).await {
+      if has_digest(&inspect) {
+        return Ok(());
+      }
+    }
+    log.state("pulling");
+    log.info("pull", &format!("Pulling image {}", image));
+    let mut pull_stream = self.docker.create_image(
+      Some(CreateImageOptions { from_image: image.clone(), ..Default::default() }),
+      None,
+      None
+    );
+    while let Some(progress) = pull_stream.next().await {
+      if let Err(e) = progress {
+        return Err(BuildError::Transient(format!("Failed to pull image {}: {}", image, e)));
+      }
+    }
+    let inspect = self.docker
+      .inspect_image(&image).await
+      .map_err(|e| BuildError::Transient(format!("Failed to inspect image {}: {}", image, e)))?;
+    if !has_digest(&inspect) {
+      error!("Pulled image {} does not match its pinned digest", image);
+      return Err(BuildError::Failed(format!("Image {} does not match its pinned digest", image)));
+    }
+    log.info("pull", &format!("Verified image digest {}", digest));
+    Ok(())
+  }
+
+  async fn run_container(
+    &self,
+    name: &str,
+    cont_conf: Config<String>,
+    stage: &str,
+    commit_as: Option<&str>,
+    log: &BuildLog
+  ) -> Result<i64, BuildError> {
+    let docker = &self.docker;
+    // Create the container with the worker specific name, removing any leftover from a previous run
+    let _ = docker.remove_container(name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+    let cont_opt = CreateContainerOptions {
+      name,
+      platform: None,
+    };
+    let container = docker
+      .create_container(Some(cont_opt), cont_conf).await
+      .map_err(|e| BuildError::Failed(format!("Failed to create compiler container: {}", e)))?;
+    if let Err(e) = docker.start_container::<String>(&container.id, None).await {
+      let _ = docker.remove_container(name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+      return Err(BuildError::Failed(format!("Failed to start compiler: {}", e)));
+    }
+    // Stream container logs concurrently while waiting for completion
+    let log_options = LogsOptions::<String> { follow: true, stdout: true, stderr: true, ..Default::default() };
+    let mut log_stream = docker.logs(name, Some(log_options));
+    let container_log = log.clone();
+    let log_stage = stage.to_string();
+    let log_task = tokio::spawn(async move {
+      while let Some(Ok(output)) = log_stream.next().await {
+        let stream = match output {
+          LogOutput::StdErr { .. } => "stderr",
+          _ => "stdout",
+        };
+        let line = output.to_string();
+        debug!("Container log: {}", line.trim_end());
+        container_log.push(&log_stage, stream, line.trim_end());
+      }
+    });
+    // Wait for the container to finish and retrieve the exit code
+    let mut stream = docker.wait_container(name, Some(WaitContainerOptions { condition: "not-running" }));
+    let result = match stream.next().await {
+      Some(Ok(ContainerWaitResponse { status_code, .. })) => Ok(status_code),
+      _ => Err(BuildError::Failed(String::from("Compilation failed with unknown exit code"))),
+    };
+    let _ = log_task.await;
+    if let (Ok(0), Some(image)) = (&result, commit_as) {
+      let (repo, tag) = image.split_once(':').unwrap_or((image, "latest"));
+      let commit_opt = CommitContainerOptions { container: name, repo, tag, pause: false, ..Default::default() };
+      if let Err(e) = docker.commit_container(commit_opt, Config::<String>::default()).await {
+        let _ = docker.remove_container(name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+        return Err(BuildError::Transient(format!("Failed to commit {} container: {}", stage, e)));
+      }
+    }
+    let _ = docker.remove_container(name, Some(RemoveContainerOptions { force: true, ..Default::default() })).await;
+    result
+  }
+
+  async fn remove_image(&self, image: &str) {
+    let _ = self.docker.remove_image(image, Some(RemoveImageOptions { force: true, ..Default::default() }), None).await;
+  }
+}
+
+/// Runner backed by the Docker-compatible API of a Podman socket, such as one of rootless Podman
+#[derive(Clone)]
+pub struct PodmanRunner {
+  inner: DockerRunner,
+}
+
+impl PodmanRunner {
+  /// Connect to the Podman socket at `socket`, defaulting to the rootless socket of the current user
+  pub fn connect(socket: Option<&str>) -> Result<Self, String> {
+    let socket = match socket {
+      Some(s) => s.to_string(),
+      None => {
+        let runtime_dir = env::var("XDG_RUNTIME_DIR").map_err(|_| String::from("XDG_RUNTIME_DIR is not set"))?;
+        format!("{}/podman/podman.sock", runtime_dir)
+      }
+    };
+    Docker::connect_with_socket(&socket, 120, API_DEFAULT_VERSION)
+      .map(|docker| PodmanRunner { inner: DockerRunner { docker } })
+      .map_err(|e| e.to_string())
+  }
+}
+
+/// Podman does not resolve short image names without a registries config, hence images are fully qualified.
+/// Committed images without a registry live under `localhost`.
+fn qualify_image(image: &str, default_registry: &str) -> String {
+  let first = image.split('/').next().unwrap_or_default();
+  let has_registry = image.contains('/') && (first.contains('.') || first.contains(':') || first == "localhost");
+  if has_registry { image.to_string() } else { format!("{}/{}", default_registry, image) }
+}
+
+/// Fully qualified name of an image run by Podman. Dependency images committed by a previous phase are local, toolchain
+/// images come from Docker Hub.
+fn podman_image(image: &str) -> String {
+  match image.split_once(':').map(|(repo, _)| repo) {
+    Some(DEPS_IMAGE) => qualify_image(image, "localhost"),
+    _ => qualify_image(image, "docker.io"),
+  }
+}
+
+#[async_trait]
+impl BuildRunner for PodmanRunner {
+  async fn pull_image(&self, repo: &str, digest: &str, log: &BuildLog) -> Result<(), BuildError> {
+    self.inner.pull_image(&qualify_image(repo, "docker.io"), digest, log).await
+  }
+
+  async fn run_container(
+    &self,
+    name: &str,
+    mut cont_conf: Config<String>,
+    stage: &str,
+    commit_as: Option<&str>,
+    log: &BuildLog
+  ) -> Result<i64, BuildError> {
+    cont_conf.image = cont_conf.image.map(|i| podman_image(&i));
+    let commit_as = commit_as.map(|i| qualify_image(i, "localhost"));
+    self.inner.run_container(name, cont_conf, stage, commit_as.as_deref(), log).await
+  }
+
+  async fn remove_image(&self, image: &str) {
+    self.inner.remove_image(&qualify_image(image, "localhost")).await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::compiler::toolchains::tinygo_image;
+  use crate::types::cv::{ asc_versions, rust_versions, tinygo_versions };
+
+  #[test]
+  fn podman_image_names() {
+    for libs in tinygo_versions.values() {
+      assert_eq!(podman_image(&tinygo_image(&libs.img_digest)), format!("docker.io/{}", tinygo_image(&libs.img_digest)));
+    }
+    for image in rust_versions.values().map(|v| &v.image).chain(asc_versions.values().map(|v| &v.image)) {
+      assert_eq!(podman_image(image), format!("docker.io/{}", image));
+    }
+    assert_eq!(podman_image("magi-cv-deps:worker-0"), "localhost/magi-cv-deps:worker-0");
+    assert_eq!(qualify_image("tinygo/tinygo", "docker.io"), "docker.io/tinygo/tinygo");
+    assert_eq!(qualify_image("ghcr.io/org/image", "docker.io"), "ghcr.io/org/image");
+    assert_eq!(qualify_image("localhost/magi-cv-deps:worker-0", "localhost"), "localhost/magi-cv-deps:worker-0");
+  }
+}
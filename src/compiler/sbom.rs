@@ -0,0 +1,230 @@
+use serde_json::{ json, Value };
+use std::{ collections::HashMap, fs, path::Path };
+use crate::types::cv::{ CVSbom, CVSbomComponent };
+
+/// Main module and dependencies of a Go module. Since Go 1.17, go.mod lists every module that provides packages to
+/// the build, so the requirements are the pruned module graph of the build.
+pub struct GoModule {
+  pub module: String,
+  pub go_version: Option<String>,
+  pub components: Vec<CVSbomComponent>,
+}
+
+/// Directive arguments of a go.mod line with comments removed and quoted paths unquoted
+fn fields(line: &str) -> Vec<String> {
+  let line = line.split("//").next().unwrap_or_default();
+  line
+    .split_whitespace()
+    .map(|f| f.trim_matches('"').to_string())
+    .collect()
+}
+
+/// Parse the module path, Go version, requirements and replacements of a go.mod file. Module checksums are taken from
+/// go.sum, keyed by the module path and version that is actually downloaded after replacements.
+pub fn parse_go_mod(go_mod: &str, go_sum: &str) -> Result<GoModule, String> {
+  let mut module = None;
+  let mut go_version = None;
+  let mut requires: Vec<(String, String, bool)> = Vec::new();
+  let mut replaces: Vec<(String, Option<String>, String, Option<String>)> = Vec::new();
+  let mut block: Option<String> = None;
+  for line in go_mod.lines() {
+    let trimmed = line.trim();
+    if trimmed == ")" {
+      block = None;
+      continue;
+    }
+    let mut args = fields(trimmed);
+    if args.is_empty() {
+      continue;
+    }
+    let directive = match &block {
+      Some(b) => b.clone(),
+      None => {
+        let d = args.remove(0);
+        if args.first().map(|a| a.as_str()) == Some("(") {
+          block = Some(d);
+          continue;
+        }
+        d
+      }
+    };
+    match directive.as_str() {
+      "module" => {
+        module = args.first().cloned();
+      }
+      "go" => {
+        go_version = args.first().cloned();
+      }
+      "require" => {
+        if args.len() < 2 {
+          return Err(format!("Invalid require directive: {}", trimmed));
+        }
+        let indirect = trimmed.contains("// indirect");
+        requires.push((args[0].clone(), args[1].clone(), indirect));
+      }
+      "replace" => {
+        // old [version] => new [version]
+        let arrow = args
+          .iter()
+          .position(|a| a == "=>")
+          .ok_or(format!("Invalid replace directive: {}", trimmed))?;
+        let (old, new) = args.split_at(arrow);
+        let new = &new[1..];
+        if old.is_empty() || new.is_empty() {
+          return Err(format!("Invalid replace directive: {}", trimmed));
+        }
+        replaces.push((old[0].clone(), old.get(1).cloned(), new[0].clone(), new.get(1).cloned()));
+      }
+      _ => (),
+    }
+  }
+  let module = module.ok_or(String::from("go.mod does not declare a module path"))?;
+  let checksums = parse_go_sum(go_sum);
+  let components = requires
+    .into_iter()
+    .map(|(path, version, indirect)| {
+      // a replacement of a specific version takes precedence over one of every version
+      let replace = replaces
+        .iter()
+        .find(|r| r.0 == path && r.1.as_deref() == Some(version.as_str()))
+        .or_else(|| replaces.iter().find(|r| r.0 == path && r.1.is_none()));
+      let (replace_path, replace_version) = match replace {
+        Some(r) => (Some(r.2.clone()), r.3.clone()),
+        None => (None, None),
+      };
+      let checksum = match (&replace_path, &replace_version) {
+        (Some(p), Some(v)) => checksums.get(&(p.clone(), v.clone())).cloned(),
+        // local directory replacements are not downloaded
+        (Some(_), None) => None,
+        _ => checksums.get(&(path.clone(), version.clone())).cloned(),
+      };
+      CVSbomComponent { path, version, checksum, indirect, replace_path, replace_version }
+    })
+    .collect();
+  Ok(GoModule { module, go_version, components })
+}
+
+/// `h1:` checksums of module contents in go.sum keyed by module path and version, go.mod checksums are skipped
+pub fn parse_go_sum(go_sum: &str) -> HashMap<(String, String), String> {
+  go_sum
+    .lines()
+    .filter_map(|line| {
+      let parts: Vec<&str> = line.split_whitespace().collect();
+      match parts.as_slice() {
+        [path, version, hash] if !version.ends_with("/go.mod") => Some(((path.to_string(), version.to_string()), hash.to_string())),
+        _ => None,
+      }
+    })
+    .collect()
+}
+
+/// Parse the Go module in `dir`, or none if it has no go.mod
+pub fn read_go_module(dir: &Path) -> Result<Option<GoModule>, String> {
+  let go_mod = match fs::read_to_string(dir.join("go.mod")) {
+    Ok(t) => t,
+    Err(_) => {
+      return Ok(None);
+    }
+  };
+  let go_sum = fs::read_to_string(dir.join("go.sum")).unwrap_or_default();
+  parse_go_mod(&go_mod, &go_sum).map(Some)
+}
+
+/// Package URL of a Go module
+fn purl(path: &str, version: &str) -> String {
+  format!("pkg:golang/{}@{}", path, version)
+}
+
+/// CycloneDX 1.5 JSON document of a verified contract SBOM
+pub fn cyclonedx(sbom: &CVSbom) -> Value {
+  let components: Vec<Value> = sbom.components
+    .iter()
+    .map(|c| {
+      let (path, version) = match (&c.replace_path, &c.replace_version) {
+        (Some(p), Some(v)) => (p.as_str(), v.as_str()),
+        _ => (c.path.as_str(), c.version.as_str()),
+      };
+      let mut properties = vec![json!({ "name": "go:indirect", "value": c.indirect.to_string() })];
+      if let Some(checksum) = &c.checksum {
+        properties.push(json!({ "name": "go:checksum", "value": checksum }));
+      }
+      if c.replace_path.is_some() {
+        properties.push(json!({ "name": "go:replaces", "value": format!("{}@{}", c.path, c.version) }));
+      }
+      json!({
+        "type": "library",
+        "bom-ref": purl(path, version),
+        "name": path,
+        "version": version,
+        "purl": purl(path, version),
+        "scope": "required",
+        "properties": properties,
+      })
+    })
+    .collect();
+  json!({
+    "bomFormat": "CycloneDX",
+    "specVersion": "1.5",
+    "version": 1,
+    "metadata": {
+      "timestamp": sbom.created_ts.to_chrono().to_rfc3339(),
+      "component": {
+        "type": "application",
+        "bom-ref": sbom.code,
+        "name": sbom.module,
+        "properties": [
+          { "name": "magi:address", "value": sbom.contract_id },
+          { "name": "magi:code", "value": sbom.code },
+        ],
+      },
+    },
+    "components": components,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn go_module_graph() {
+    let go_mod =
+      r#"module github.com/example/token // contract
+
+go 1.23.0
+
+require github.com/vsc-eco/go-contract-template v0.1.2
+
+require (
+	github.com/JustinKnueppel/go-result v0.0.1 // indirect
+	"golang.org/x/crypto" v0.30.0
+	example.com/local v1.0.0
+)
+
+replace golang.org/x/crypto v0.30.0 => golang.org/x/crypto v0.31.0
+replace example.com/local => ../local
+"#;
+    let go_sum =
+      r#"github.com/vsc-eco/go-contract-template v0.1.2 h1:AAAA=
+github.com/vsc-eco/go-contract-template v0.1.2/go.mod h1:BBBB=
+golang.org/x/crypto v0.30.0 h1:CCCC=
+golang.org/x/crypto v0.31.0 h1:DDDD=
+"#;
+    let module = parse_go_mod(go_mod, go_sum).unwrap();
+    assert_eq!(module.module, "github.com/example/token");
+    assert_eq!(module.go_version.as_deref(), Some("1.23.0"));
+    assert_eq!(module.components.len(), 4);
+    let template = &module.components[0];
+    assert_eq!((template.path.as_str(), template.version.as_str()), ("github.com/vsc-eco/go-contract-template", "v0.1.2"));
+    assert_eq!(template.checksum.as_deref(), Some("h1:AAAA="));
+    assert!(!template.indirect);
+    assert!(module.components[1].indirect);
+    let crypto = &module.components[2];
+    assert_eq!(crypto.replace_version.as_deref(), Some("v0.31.0"));
+    assert_eq!(crypto.checksum.as_deref(), Some("h1:DDDD="));
+    let local = &module.components[3];
+    assert_eq!(local.replace_path.as_deref(), Some("../local"));
+    assert!(local.checksum.is_none());
+    assert!(parse_go_mod("go 1.23.0\n", "").is_err());
+  }
+}
@@ -0,0 +1,69 @@
+use std::{ collections::BTreeMap, fs };
+use crate::types::cv::{ CVSearchFileLines, CVSourceFile };
+use super::snapshot::blob_path;
+
+/// Files larger than this are not indexed
+const MAX_FILE_SIZE: u64 = 1048576;
+
+/// Maximum number of line numbers recorded per token and file
+const MAX_LINES: usize = 256;
+
+const MIN_TOKEN_LEN: usize = 2;
+const MAX_TOKEN_LEN: usize = 64;
+
+/// Lowercased identifiers and words of a text, in order of appearance. Tokens are split on any character other than
+/// alphanumerics and underscores, so `sdk.Transfer("ERC20")` yields `sdk`, `transfer` and `erc20`.
+pub fn tokenize(text: &str) -> Vec<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric() && c != '_')
+    .filter(|t| t.len() >= MIN_TOKEN_LEN && t.len() <= MAX_TOKEN_LEN)
+    .map(|t| t.to_lowercase())
+    .collect()
+}
+
+/// Line numbers starting from 1 of each token of a text file
+pub fn index_text(text: &str) -> BTreeMap<String, Vec<u32>> {
+  let mut postings: BTreeMap<String, Vec<u32>> = BTreeMap::new();
+  for (i, line) in text.lines().enumerate() {
+    let line_no = (i as u32) + 1;
+    for token in tokenize(line) {
+      let lines = postings.entry(token).or_default();
+      if lines.last() != Some(&line_no) && lines.len() < MAX_LINES {
+        lines.push(line_no);
+      }
+    }
+  }
+  postings
+}
+
+/// Postings of the text files of a snapshotted source tree keyed by token, read from the content-addressed store.
+/// Binary and oversized files are skipped.
+pub fn index_tree(store_dir: &str, files: &[CVSourceFile]) -> BTreeMap<String, Vec<CVSearchFileLines>> {
+  let mut index: BTreeMap<String, Vec<CVSearchFileLines>> = BTreeMap::new();
+  for file in files.iter().filter(|f| f.size <= MAX_FILE_SIZE) {
+    let text = match fs::read(blob_path(store_dir, &file.sha256)).ok().and_then(|d| String::from_utf8(d).ok()) {
+      Some(t) => t,
+      None => {
+        continue;
+      }
+    };
+    for (token, lines) in index_text(&text) {
+      index.entry(token).or_default().push(CVSearchFileLines { path: file.path.clone(), sha256: file.sha256.clone(), lines });
+    }
+  }
+  index
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn tokenize_source() {
+    assert_eq!(tokenize("sdk.Transfer(\"ERC20\", x)"), vec!["sdk", "transfer", "erc20"]);
+    let index = index_text("package main\n\nfunc Transfer() {\n  sdk.Transfer(x)\n}\n");
+    assert_eq!(index["transfer"], vec![3, 4]);
+    assert_eq!(index["sdk"], vec![4]);
+    assert!(!index.contains_key("x"));
+  }
+}
@@ -0,0 +1,277 @@
+use regex::Regex;
+use serde::{ Deserialize, Serialize };
+use std::{ collections::HashMap, fs, path::Path };
+use utoipa::ToSchema;
+use crate::config::CompilerConf;
+use crate::types::{ cv::{ asc_versions, rust_versions, CVContract, CVTinyGoLibVersions }, server::RespErr };
+use super::post_process;
+
+/// Build manifest in the source root, used when a request does not specify build settings
+pub const MANIFEST_FILE: &str = "magi-contract.toml";
+
+/// Maximum size of the build manifest in bytes
+const MANIFEST_MAX_SIZE: u64 = 65536;
+
+/// AssemblyScript compiler options that may be passed through `asc_options`
+const ASC_ALLOWED_OPTIONS: [&str; 21] = [
+  "-O0",
+  "-O1",
+  "-O2",
+  "-O3",
+  "-Os",
+  "-Oz",
+  "--optimize",
+  "--optimizeLevel",
+  "--shrinkLevel",
+  "--converge",
+  "--noAssert",
+  "--uncheckedBehavior",
+  "--runtime",
+  "--exportRuntime",
+  "--exportStart",
+  "--exportTable",
+  "--noExportMemory",
+  "--importMemory",
+  "--initialMemory",
+  "--maximumMemory",
+  "--enable",
+];
+
+fn is_valid_asc_option(opt: &str) -> bool {
+  let value_regex: Regex = Regex::new(r"^[A-Za-z0-9_.,\-]+$").expect("Invalid regex pattern");
+  match opt.split_once('=') {
+    Some((name, value)) => ASC_ALLOWED_OPTIONS.contains(&name) && name.starts_with("--") && value_regex.is_match(value),
+    None => ASC_ALLOWED_OPTIONS.contains(&opt),
+  }
+}
+
+/// Toolchain and build settings of a contract verification request or manifest
+#[derive(Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct BuildSettings {
+  /// TinyGo version. Required for Go contracts.
+  pub tinygo_version: Option<String>,
+  /// Rust compiler version. Required for Rust contracts.
+  pub rust_version: Option<String>,
+  /// Cargo profile used to build the contract. Defaults to `release` if not specified. Rust contracts only.
+  pub cargo_profile: Option<String>,
+  /// Cargo features to enable when building the contract. Rust contracts only.
+  pub cargo_features: Option<Vec<String>>,
+  /// AssemblyScript compiler version. Required for AssemblyScript contracts.
+  pub asc_version: Option<String>,
+  /// Entry file relative to the contract directory. Defaults to `assembly/index.ts` if not specified. AssemblyScript contracts only.
+  pub asc_entry: Option<String>,
+  /// Additional AssemblyScript compiler options in `--name` or `--name=value` form. AssemblyScript contracts only.
+  pub asc_options: Option<Vec<String>>,
+  /// Post-processing steps applied in order to the compiled output before stripping.
+  pub post_process: Option<Vec<PostProcessStep>>,
+  /// Tool used to strip output WASM file. Valid values: `wabt` or `wasm-tools`.
+  pub strip_tool: Option<String>,
+  /// Subdirectory within the source tree containing the contract source code. For Go contracts this is relative to `go_mod_dir` and defaults to `contract`, otherwise defaults to the source root.
+  pub contract_dir: Option<String>,
+  /// Subdirectory within the source tree containing the Go module (go.mod). Defaults to the source root if not specified.
+  pub go_mod_dir: Option<String>,
+}
+
+/// Post-processing tool run on the compiled output
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct PostProcessStep {
+  /// Tool name. Valid values: `wasm-opt`, if enabled on this server.
+  pub tool: String,
+  /// Allowlisted flags passed to the tool, such as `-Oz` or `--strip-debug`.
+  pub flags: Option<Vec<String>>,
+}
+
+impl BuildSettings {
+  /// Whether no build setting is specified
+  pub fn is_empty(&self) -> bool {
+    self.tinygo_version.is_none() &&
+      self.rust_version.is_none() &&
+      self.cargo_profile.is_none() &&
+      self.cargo_features.is_none() &&
+      self.asc_version.is_none() &&
+      self.asc_entry.is_none() &&
+      self.asc_options.is_none() &&
+      self.post_process.is_none() &&
+      self.strip_tool.is_none() &&
+      self.contract_dir.is_none() &&
+      self.go_mod_dir.is_none()
+  }
+}
+
+/// Declarative build settings committed to the source tree as `magi-contract.toml`
+#[derive(Clone, Deserialize)]
+pub struct ContractManifest {
+  /// Contract language, must match the runtime of the deployed contract if specified
+  pub lang: Option<String>,
+  #[serde(flatten)]
+  pub settings: BuildSettings,
+}
+
+/// Read the build manifest from the source root. Symlinks are rejected as they may point outside of the source tree.
+pub fn read_manifest(src_dir: &Path) -> Result<Option<ContractManifest>, String> {
+  let path = src_dir.join(MANIFEST_FILE);
+  let metadata = match fs::symlink_metadata(&path) {
+    Ok(m) => m,
+    Err(_) => {
+      return Ok(None);
+    }
+  };
+  if !metadata.is_file() {
+    return Err(format!("{} is not a regular file", MANIFEST_FILE));
+  }
+  if metadata.len() > MANIFEST_MAX_SIZE {
+    return Err(format!("{} is larger than {} bytes", MANIFEST_FILE, MANIFEST_MAX_SIZE));
+  }
+  let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))?;
+  toml::from_str(&text).map(Some).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))
+}
+
+/// Validates the build settings for the language of the contract and applies them to the verification record
+pub fn apply_build_settings(
+  cv: &mut CVContract,
+  settings: &BuildSettings,
+  tinygo_versions: &HashMap<String, CVTinyGoLibVersions>,
+  conf: Option<&CompilerConf>
+) -> Result<(), RespErr> {
+  if let Some(tool) = &settings.strip_tool {
+    if tool != "wabt" && tool != "wasm-tools" {
+      return Err(RespErr::CvInvalidWasmStripTool);
+    }
+  }
+  let post_process = match &settings.post_process {
+    Some(steps) if !steps.is_empty() => {
+      let steps: Vec<(String, Vec<String>)> = steps
+        .iter()
+        .map(|s| (s.tool.clone(), s.flags.clone().unwrap_or_default()))
+        .collect();
+      let tools = conf.and_then(|c| c.post_process_tools.as_ref());
+      Some(post_process::pin_steps(&steps, tools).map_err(|e| RespErr::CvInvalidPostProcess { msg: e })?)
+    }
+    _ => None,
+  };
+  if let Some(ref dir) = settings.contract_dir {
+    let contract_dir_regex: Regex = Regex::new(r"^[A-Za-z0-9/_.\-]+$").expect("Invalid regex pattern");
+    if dir.is_empty() || dir.contains("..") || dir.starts_with('/') || !contract_dir_regex.is_match(dir) {
+      return Err(RespErr::CvInvalidContractDir);
+    }
+  }
+  cv.post_process = post_process;
+  cv.strip_tool = settings.strip_tool.clone();
+  cv.contract_dir = settings.contract_dir.clone();
+  let path_regex: Regex = Regex::new(r"^[A-Za-z0-9/_.\-]+$").expect("Invalid regex pattern");
+  match cv.lang.as_str() {
+    "go" => {
+      if let Some(ref dir) = settings.go_mod_dir {
+        if dir.is_empty() || dir.contains("..") || dir.starts_with('/') || !path_regex.is_match(dir) {
+          return Err(RespErr::CvInvalidGoModDir);
+        }
+      }
+      let tinygo_version = settings.tinygo_version.clone().unwrap_or_default();
+      let tinygo_libs = tinygo_versions.get(&tinygo_version).ok_or(RespErr::CvInvalidTinyGoVersion)?.clone();
+      cv.tinygo_version = Some(tinygo_version);
+      cv.go_version = Some(tinygo_libs.go);
+      cv.llvm_version = Some(tinygo_libs.llvm);
+      cv.tinygo_img_digest = Some(tinygo_libs.img_digest);
+      cv.toolchain_img_digest = None;
+      cv.go_mod_dir = settings.go_mod_dir.clone();
+    }
+    "rust" => {
+      let rust_version = settings.rust_version.clone().unwrap_or_default();
+      let rust_libs = rust_versions.get(&rust_version).ok_or(RespErr::CvInvalidRustVersion)?.clone();
+      let cargo_name_regex: Regex = Regex::new(r"^[A-Za-z0-9_\-]+$").expect("Invalid regex pattern");
+      let cargo_feature_regex: Regex = Regex::new(r"^[A-Za-z0-9_\-]+(/[A-Za-z0-9_\-]+)?$").expect("Invalid regex pattern");
+      if let Some(ref profile) = settings.cargo_profile {
+        if profile.len() > 64 || !cargo_name_regex.is_match(profile) {
+          return Err(RespErr::CvInvalidCargoProfile);
+        }
+      }
+      if let Some(ref features) = settings.cargo_features {
+        if features.len() > 32 || features.iter().any(|f| f.len() > 64 || !cargo_feature_regex.is_match(f)) {
+          return Err(RespErr::CvInvalidCargoFeature);
+        }
+      }
+      cv.rust_version = Some(rust_version);
+      cv.llvm_version = Some(rust_libs.llvm);
+      cv.toolchain_img_digest = Some(rust_libs.img_digest);
+      cv.cargo_profile = Some(settings.cargo_profile.clone().unwrap_or(String::from("release")));
+      cv.cargo_features = settings.cargo_features.clone().filter(|f| !f.is_empty());
+    }
+    "assemblyscript" => {
+      let asc_version = settings.asc_version.clone().unwrap_or_default();
+      let asc_libs = asc_versions.get(&asc_version).ok_or(RespErr::CvInvalidAscVersion)?.clone();
+      if let Some(ref entry) = settings.asc_entry {
+        if entry.is_empty() || entry.contains("..") || entry.starts_with('/') || !path_regex.is_match(entry) {
+          return Err(RespErr::BadRequest { msg: String::from("Invalid AssemblyScript entry file path") });
+        }
+      }
+      if let Some(ref opts) = settings.asc_options {
+        if opts.len() > 32 || opts.iter().any(|o| !is_valid_asc_option(o)) {
+          return Err(RespErr::CvInvalidAscOption);
+        }
+      }
+      cv.asc_version = Some(asc_version);
+      cv.node_version = Some(asc_libs.node);
+      cv.toolchain_img_digest = Some(asc_libs.img_digest);
+      cv.asc_entry = Some(settings.asc_entry.clone().unwrap_or(String::from("assembly/index.ts")));
+      cv.asc_options = settings.asc_options.clone().filter(|o| !o.is_empty());
+    }
+    _ => {
+      return Err(RespErr::BadRequest { msg: String::from("Language is currently unsupported") });
+    }
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn cv_contract(lang: &str) -> CVContract {
+    bson
+      ::from_document(
+        bson::doc! {
+        "_id": "bafkreiexample",
+        "contract_id": "vsc1example",
+        "repo_name": "",
+        "repo_branch": "",
+        "git_commit": null,
+        "request_ts": bson::DateTime::from_millis(0),
+        "verified_ts": null,
+        "status": "queued",
+        "exports": null,
+        "license": null,
+        "lang": lang,
+      }
+      )
+      .unwrap()
+  }
+
+  #[test]
+  fn manifest_settings() {
+    let manifest: ContractManifest = toml
+      ::from_str(
+        r#"
+lang = "rust"
+rust_version = "1.88.0"
+contract_dir = "contracts/token"
+strip_tool = "wasm-tools"
+"#
+      )
+      .unwrap();
+    assert_eq!(manifest.lang.as_deref(), Some("rust"));
+    assert!(!manifest.settings.is_empty());
+    let mut cv = cv_contract("rust");
+    let rust_version = rust_versions.keys().next().unwrap().clone();
+    let settings = BuildSettings { rust_version: Some(rust_version.clone()), ..manifest.settings.clone() };
+    assert!(apply_build_settings(&mut cv, &settings, &HashMap::new(), None).is_ok());
+    assert_eq!(cv.rust_version, Some(rust_version));
+    assert_eq!(cv.cargo_profile.as_deref(), Some("release"));
+    assert_eq!(cv.contract_dir.as_deref(), Some("contracts/token"));
+
+    let traversal = BuildSettings { contract_dir: Some(String::from("../outside")), ..settings.clone() };
+    assert!(matches!(apply_build_settings(&mut cv, &traversal, &HashMap::new(), None), Err(RespErr::CvInvalidContractDir)));
+    let unknown = BuildSettings { rust_version: Some(String::from("0.0.1")), ..settings };
+    assert!(matches!(apply_build_settings(&mut cv, &unknown, &HashMap::new(), None), Err(RespErr::CvInvalidRustVersion)));
+    assert!(BuildSettings::default().is_empty());
+  }
+}
@@ -0,0 +1,116 @@
+use rand::Rng;
+use sha2::{ Digest, Sha256 };
+use std::{ fs, path::{ Path, PathBuf } };
+use crate::types::cv::CVSourceFile;
+
+/// Path of a blob in the content-addressed source store
+pub fn blob_path(store_dir: &str, sha256: &str) -> PathBuf {
+  Path::new(store_dir).join(&sha256[..2]).join(sha256)
+}
+
+fn walk(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<(), String> {
+  for entry in fs::read_dir(dir).map_err(|e| e.to_string())? {
+    let entry = entry.map_err(|e| e.to_string())?;
+    let file_type = entry.file_type().map_err(|e| e.to_string())?;
+    let path = entry.path();
+    if file_type.is_dir() {
+      if entry.file_name() != ".git" {
+        walk(root, &path, files)?;
+      }
+    } else if file_type.is_file() {
+      // symlinks are skipped as they may point outside of the source tree
+      let rel = path.strip_prefix(root).map_err(|e| e.to_string())?;
+      if let Some(rel) = rel.to_str() {
+        files.push(rel.to_string());
+      }
+    }
+  }
+  Ok(())
+}
+
+/// List regular files within the source tree excluding the `.git` directory, sorted by path
+pub fn list_files(root: &Path) -> Result<Vec<String>, String> {
+  let mut files = Vec::new();
+  walk(root, root, &mut files)?;
+  files.sort();
+  Ok(files)
+}
+
+/// Copy data into the content-addressed store unless already present, returning its SHA-256 hash
+pub fn store_blob(data: &[u8], store_dir: &str) -> Result<String, String> {
+  let mut hasher = Sha256::new();
+  hasher.update(data);
+  let sha256 = hex::encode(&hasher.finalize()[..]);
+  let blob = blob_path(store_dir, &sha256);
+  if blob.exists() {
+    return Ok(sha256);
+  }
+  fs::create_dir_all(blob.parent().unwrap()).map_err(|e| e.to_string())?;
+  // write to a temporary file unique to this writer first so that a partially written blob is never served, and
+  // concurrent writers of the same blob do not interleave
+  let tmp = blob.with_extension(format!("{}-{}.tmp", std::process::id(), hex::encode(rand::rng().random::<[u8; 8]>())));
+  fs::write(&tmp, data).map_err(|e| e.to_string())?;
+  if let Err(e) = fs::rename(&tmp, &blob) {
+    let _ = fs::remove_file(&tmp);
+    return Err(e.to_string());
+  }
+  Ok(sha256)
+}
+
+/// Copy the listed files into the content-addressed store, returning the manifest of the snapshot
+pub fn store_snapshot(root: &Path, files: &[String], store_dir: &str) -> Result<Vec<CVSourceFile>, String> {
+  let mut manifest = Vec::with_capacity(files.len());
+  for path in files {
+    let data = fs::read(root.join(path)).map_err(|e| format!("{}: {}", path, e))?;
+    let sha256 = store_blob(&data, store_dir)?;
+    manifest.push(CVSourceFile { path: path.clone(), size: data.len() as u64, sha256 });
+  }
+  Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn snapshot_dedup() {
+    let base = std::env::temp_dir().join(format!("magi-bb-snapshot-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&base);
+    let src = base.join("src");
+    let store = base.join("store");
+    fs::create_dir_all(src.join(".git")).unwrap();
+    fs::create_dir_all(src.join("contract")).unwrap();
+    fs::write(src.join(".git/HEAD"), "ref").unwrap();
+    fs::write(src.join("go.mod"), "module x").unwrap();
+    fs::write(src.join("contract/main.go"), "package main").unwrap();
+    fs::write(src.join("contract/copy.go"), "package main").unwrap();
+
+    let files = list_files(&src).unwrap();
+    assert_eq!(files, vec!["contract/copy.go", "contract/main.go", "go.mod"]);
+    let manifest = store_snapshot(&src, &files, store.to_str().unwrap()).unwrap();
+    assert_eq!(manifest[0].sha256, manifest[1].sha256);
+    assert_eq!(fs::read(blob_path(store.to_str().unwrap(), &manifest[2].sha256)).unwrap(), b"module x");
+    assert_eq!(fs::read_dir(&store).unwrap().count(), 2);
+    fs::remove_dir_all(&base).unwrap();
+  }
+
+  #[test]
+  fn concurrent_blob_writers() {
+    let store = std::env::temp_dir().join(format!("magi-bb-blob-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&store);
+    let store_dir = store.to_str().unwrap().to_string();
+    let data = vec![7u8; 65536];
+    let writers: Vec<_> = (0..8)
+      .map(|_| {
+        let (data, store_dir) = (data.clone(), store_dir.clone());
+        std::thread::spawn(move || store_blob(&data, &store_dir).unwrap())
+      })
+      .collect();
+    let hashes: Vec<String> = writers.into_iter().map(|w| w.join().unwrap()).collect();
+    assert!(hashes.iter().all(|h| h == &hashes[0]));
+    let blob = blob_path(&store_dir, &hashes[0]);
+    assert_eq!(fs::read(&blob).unwrap(), data);
+    assert_eq!(fs::read_dir(blob.parent().unwrap()).unwrap().count(), 1);
+    fs::remove_dir_all(&store).unwrap();
+  }
+}
@@ -0,0 +1,119 @@
+use futures_util::StreamExt;
+use log::warn;
+use regex::Regex;
+use std::collections::HashMap;
+use crate::config::CompilerConf;
+use crate::mongo::MongoDB;
+use crate::types::cv::{ asc_versions, rust_versions, tinygo_versions, CVContract, CVTinyGoLibVersions };
+
+/// Docker Hub repository of TinyGo images
+pub const TINYGO_IMAGE: &str = "tinygo/tinygo";
+
+pub fn is_valid_digest(digest: &str) -> bool {
+  let digest_regex: Regex = Regex::new(r"^sha256:[0-9a-f]{64}$").expect("Invalid regex pattern");
+  digest_regex.is_match(digest)
+}
+
+/// TinyGo image reference pinned by digest
+pub fn tinygo_image(digest: &str) -> String {
+  format!("{}@{}", TINYGO_IMAGE, digest)
+}
+
+/// Repository of an image reference without its tag
+pub fn image_repo(image: &str) -> &str {
+  image.split_once(':').map(|(repo, _)| repo).unwrap_or(image)
+}
+
+/// Image reference of a tagged image pinned by digest
+pub fn pinned_image(image: &str, digest: &str) -> String {
+  format!("{}@{}", image_repo(image), digest)
+}
+
+/// Tagged toolchain image of a Rust or AssemblyScript contract and the digest it is pinned to, recorded with the
+/// request or listed in the version table for requests made before digests were recorded
+pub fn toolchain_image(contract: &CVContract) -> Result<(String, String), String> {
+  let (image, listed) = match contract.lang.as_str() {
+    "rust" => {
+      let version = contract.rust_version.clone().ok_or(String::from("Missing Rust version"))?;
+      let libs = rust_versions.get(&version).ok_or(format!("Unsupported Rust version {}", version))?;
+      (libs.image.clone(), libs.img_digest.clone())
+    }
+    "assemblyscript" => {
+      let version = contract.asc_version.clone().ok_or(String::from("Missing AssemblyScript compiler version"))?;
+      let libs = asc_versions.get(&version).ok_or(format!("Unsupported AssemblyScript version {}", version))?;
+      (libs.image.clone(), libs.img_digest.clone())
+    }
+    lang => {
+      return Err(format!("Unsupported contract language {}", lang));
+    }
+  };
+  let digest = contract.toolchain_img_digest.clone().unwrap_or(listed);
+  if !is_valid_digest(&digest) {
+    return Err(format!("Image {} is not pinned to a valid digest", image));
+  }
+  Ok((image, digest))
+}
+
+/// TinyGo versions from the compiler config, or the built-in table if not configured
+pub fn configured_tinygo_versions(conf: Option<&CompilerConf>) -> HashMap<String, CVTinyGoLibVersions> {
+  match conf.and_then(|c| c.tinygo_versions.as_ref()) {
+    Some(versions) =>
+      versions
+        .iter()
+        .filter(|(version, libs)| {
+          let valid = is_valid_digest(&libs.img_digest);
+          if !valid {
+            warn!("Ignoring TinyGo {} from config due to invalid image digest {}", version, libs.img_digest);
+          }
+          valid
+        })
+        .map(|(version, libs)| (version.clone(), libs.clone()))
+        .collect(),
+    None => tinygo_versions.clone(),
+  }
+}
+
+/// Supported TinyGo versions, with entries from the admin-managed `tinygo_versions` collection taking precedence over configured ones
+pub async fn tinygo_version_table(
+  db: &MongoDB,
+  conf: Option<&CompilerConf>
+) -> Result<HashMap<String, CVTinyGoLibVersions>, mongodb::error::Error> {
+  let mut versions = configured_tinygo_versions(conf);
+  let mut records = db.cv_tinygo_versions.find(bson::doc! {}).await?;
+  while let Some(record) = records.next().await {
+    let record = record?;
+    if !is_valid_digest(&record.img_digest) {
+      warn!("Ignoring TinyGo {} from database due to invalid image digest {}", record.version, record.img_digest);
+      continue;
+    }
+    versions.insert(record.version, CVTinyGoLibVersions { go: record.go, llvm: record.llvm, img_digest: record.img_digest });
+  }
+  Ok(versions)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn digest_validation() {
+    assert!(is_valid_digest("sha256:98447dff0e56426b98f96a1d47ac7c1d82d27e3cd630cba81732cfc13c9a410f"));
+    assert!(!is_valid_digest("sha256:98447dff"));
+    assert!(!is_valid_digest("0.40.1"));
+    assert!(tinygo_versions.values().all(|v| is_valid_digest(&v.img_digest)));
+    assert_eq!(configured_tinygo_versions(None).len(), tinygo_versions.len());
+  }
+
+  #[test]
+  fn pinned_toolchain_images() {
+    let digest = "sha256:98447dff0e56426b98f96a1d47ac7c1d82d27e3cd630cba81732cfc13c9a410f";
+    assert_eq!(pinned_image("rust:1.88.0-slim-bookworm", digest), format!("rust@{}", digest));
+  }
+
+  #[test]
+  #[ignore = "digests of the built-in Rust and AssemblyScript images are yet to be filled in"]
+  fn toolchain_table_digests() {
+    assert!(rust_versions.values().all(|v| is_valid_digest(&v.img_digest)));
+    assert!(asc_versions.values().all(|v| is_valid_digest(&v.img_digest)));
+  }
+}
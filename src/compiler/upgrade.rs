@@ -0,0 +1,239 @@
+use bson::doc;
+use chrono::Utc;
+use futures_util::StreamExt;
+use log::{ error, info };
+use std::collections::{ HashMap, HashSet };
+use crate::config::CompilerConf;
+use crate::mongo::MongoDB;
+use crate::types::cv::{ CVAttempt, CVContract, CVStatus };
+use super::git_host::{ list_tags, newest_tag, parse_repo_url, GitRef };
+
+/// Maximum number of upgraded contracts queued for re-verification per run
+const WATCH_BATCH: usize = 50;
+/// Number of verified addresses whose latest deployed code is looked up at once
+const WATCH_LOOKUP_CHUNK: usize = 500;
+
+/// Revision to re-verify upgraded code from. A verification of a tag moves on to the newest version tag with the same
+/// path prefix, a verification of an exact commit moves on to the head of the default branch and a verification of a
+/// branch builds its current head.
+fn upgrade_ref(previous: &CVContract, tags: &[String]) -> GitRef {
+  match (&previous.repo_tag, &previous.repo_commit) {
+    (Some(tag), _) =>
+      match newest_tag(tags, tag) {
+        Some(t) => GitRef::Tag(t),
+        None => GitRef::DefaultBranch,
+      }
+    (None, Some(_)) => GitRef::DefaultBranch,
+    (None, None) if previous.repo_branch.is_empty() => GitRef::DefaultBranch,
+    (None, None) => GitRef::Branch(previous.repo_branch.clone()),
+  }
+}
+
+/// Verification request of the upgraded code with the settings of the previous verification
+fn upgrade_request(previous: &CVContract, code: &str, git_ref: GitRef) -> CVContract {
+  let (repo_branch, repo_tag, repo_commit) = match git_ref {
+    GitRef::DefaultBranch => (String::new(), None, None),
+    GitRef::Branch(b) => (b, None, None),
+    GitRef::Tag(t) => (String::new(), Some(t), None),
+    GitRef::Commit(c) => (String::new(), None, Some(c.to_string())),
+  };
+  CVContract {
+    code: code.to_string(),
+    repo_branch,
+    repo_tag,
+    repo_commit,
+    git_commit: None,
+    request_ts: bson::DateTime::from_chrono(Utc::now()),
+    verified_ts: None,
+    status: CVStatus::Queued.to_string(),
+    attempts: 0,
+    started_ts: None,
+    next_attempt_ts: None,
+    completed_ts: None,
+    last_error: None,
+    exports: None,
+    license: None,
+    gitea_url: None,
+    gitea_error: None,
+    gitea_attempts: 0,
+    bytecode_diff: None,
+    attempt_id: None,
+    upgraded_from: Some(previous.code.clone()),
+    ..previous.clone()
+  }
+}
+
+/// Queue a re-verification for contracts whose latest deployed code differs from their most recently verified code,
+/// reusing the build settings of that verification against the newest revision of its repository. The request is made
+/// on behalf of the previous verifier, who is notified through their webhook once it finishes. Contracts verified from
+/// uploaded archives are skipped as there is no repository to follow. Returns the number of queued verifications.
+pub async fn watch(db: &MongoDB, options: &CompilerConf) -> usize {
+  // only the most recent verification of each address is followed, most recently verified first
+  let pipeline = vec![
+    doc! { "$match": { "status": CVStatus::Success.to_string(), "source_archive": null } },
+    doc! { "$sort": { "verified_ts": -1 } },
+    doc! { "$group": { "_id": "$contract_id", "latest": { "$first": "$$ROOT" } } },
+    doc! { "$replaceRoot": { "newRoot": "$latest" } },
+    doc! { "$sort": { "verified_ts": -1 } }
+  ];
+  let mut cursor = match db.cv_contracts.aggregate(pipeline).await {
+    Ok(c) => c,
+    Err(e) => {
+      error!("Failed to query verified contracts: {}", e);
+      return 0;
+    }
+  };
+  let mut verified = Vec::new();
+  while let Some(previous) = cursor.next().await {
+    if let Some(c) = previous.ok().and_then(|d| bson::from_document::<CVContract>(d).ok()) {
+      verified.push(c);
+    }
+  }
+  let mut upgraded = Vec::new();
+  for chunk in verified.chunks(WATCH_LOOKUP_CHUNK) {
+    let ids: Vec<String> = chunk.iter().map(|c| c.contract_id.clone()).collect();
+    let mut latest_codes = HashMap::new();
+    match db.contracts.find(doc! { "id": { "$in": ids }, "latest": true }).await {
+      Ok(mut cursor) => {
+        while let Some(latest) = cursor.next().await {
+          if let Ok(c) = latest {
+            latest_codes.insert(c.id, c.code);
+          }
+        }
+      }
+      Err(e) => {
+        error!("Failed to retrieve latest code of verified contracts: {}", e);
+        continue;
+      }
+    }
+    let changed: Vec<(&CVContract, String)> = chunk
+      .iter()
+      .filter_map(|previous| {
+        let code = latest_codes.remove(&previous.contract_id)?;
+        Some((previous, code)).filter(|(p, c)| &p.code != c)
+      })
+      .collect();
+    if changed.is_empty() {
+      continue;
+    }
+    // the new code is either verified already, in progress or failed and left to its verifier to retry
+    let codes: Vec<String> = changed.iter().map(|(_, code)| code.clone()).collect();
+    let requested: HashSet<String> = match db.cv_contracts.distinct("_id", doc! { "_id": { "$in": codes } }).await {
+      Ok(ids) => ids.into_iter().filter_map(|id| id.as_str().map(String::from)).collect(),
+      Err(e) => {
+        error!("Failed to check verifications of upgraded contract code: {}", e);
+        continue;
+      }
+    };
+    upgraded.extend(
+      changed
+        .into_iter()
+        .filter(|(_, code)| !requested.contains(code))
+        .map(|(previous, code)| (previous.clone(), code))
+    );
+    if upgraded.len() >= WATCH_BATCH {
+      upgraded.truncate(WATCH_BATCH);
+      break;
+    }
+  }
+  let mut queued = 0;
+  for (previous, code) in upgraded {
+    let repo_url = previous.repo_url.clone().unwrap_or(format!("https://github.com/{}", previous.repo_name));
+    let repo = match parse_repo_url(&repo_url, options.allowed_git_hosts.as_ref()) {
+      Some(r) => r,
+      None => {
+        error!("Skipping re-verification of contract {}: invalid repository URL {}", previous.contract_id, repo_url);
+        continue;
+      }
+    };
+    let tags = match previous.repo_tag {
+      Some(_) => {
+        // listing the remote tags is a blocking network call
+        let url = repo.url.clone();
+        let listed = tokio::task::spawn_blocking(move || list_tags(&url).map_err(|e| e.to_string())).await;
+        match listed.map_err(|e| e.to_string()).and_then(|r| r) {
+          Ok(t) => t,
+          Err(e) => {
+            error!("Failed to list tags of {} for re-verification of contract {}: {}", repo.url, previous.contract_id, e);
+            continue;
+          }
+        }
+      }
+      None => Vec::new(),
+    };
+    let git_ref = upgrade_ref(&previous, &tags);
+    let mut request = upgrade_request(&previous, &code, git_ref.clone());
+    request.repo_url = Some(repo.url);
+    let attempt = CVAttempt::queued(&request);
+    request.attempt_id = Some(attempt.id);
+    // a verification of the new code requested in the meantime takes precedence
+    if let Err(e) = db.cv_contracts.insert_one(&request).await {
+      error!("Failed to queue re-verification of contract {}: {}", previous.contract_id, e);
+      continue;
+    }
+    if let Err(e) = db.cv_attempts.insert_one(attempt).await {
+      // the request would otherwise refer to an attempt that does not exist, it is queued again on the next run
+      error!("Failed to record re-verification attempt of contract {}: {}", previous.contract_id, e);
+      if let Err(e) = db.cv_contracts.delete_one(doc! { "_id": &code, "attempt_id": request.attempt_id }).await {
+        error!("Failed to remove re-verification request of contract {}: {}", previous.contract_id, e);
+      }
+      continue;
+    }
+    info!("Queued re-verification of upgraded contract {} code {} from {}", previous.contract_id, code, git_ref);
+    queued += 1;
+  }
+  queued
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn verified(branch: &str, tag: Option<&str>, commit: Option<&str>) -> CVContract {
+    bson
+      ::from_document(
+        bson::doc! {
+        "_id": "bafkreiprevious",
+        "contract_id": "vsc1example",
+        "verifier": "alice",
+        "repo_url": "https://github.com/example/contract",
+        "repo_name": "example/contract",
+        "repo_branch": branch,
+        "repo_tag": tag,
+        "repo_commit": commit,
+        "git_commit": "0123456789012345678901234567890123456789",
+        "request_ts": bson::DateTime::from_millis(0),
+        "verified_ts": bson::DateTime::from_millis(0),
+        "status": "success",
+        "exports": ["transfer"],
+        "license": "MIT",
+        "lang": "go",
+        "tinygo_version": "0.38.0",
+      }
+      )
+      .unwrap()
+  }
+
+  #[test]
+  fn upgrade_revision() {
+    let tags = vec![String::from("v1.0.0"), String::from("v1.1.0")];
+    assert_eq!(upgrade_ref(&verified("", Some("v1.0.0"), None), &tags), GitRef::Tag(String::from("v1.1.0")));
+    assert_eq!(upgrade_ref(&verified("", Some("release"), None), &[]), GitRef::DefaultBranch);
+    assert_eq!(upgrade_ref(&verified("main", None, None), &tags), GitRef::Branch(String::from("main")));
+    let commit = "0123456789012345678901234567890123456789";
+    assert_eq!(upgrade_ref(&verified("", None, Some(commit)), &tags), GitRef::DefaultBranch);
+  }
+
+  #[test]
+  fn upgrade_request_settings() {
+    let previous = verified("", Some("v1.0.0"), None);
+    let request = upgrade_request(&previous, "bafkreiupgraded", GitRef::Tag(String::from("v1.1.0")));
+    assert_eq!(request.code, "bafkreiupgraded");
+    assert_eq!(request.upgraded_from.as_deref(), Some("bafkreiprevious"));
+    assert_eq!(request.repo_tag.as_deref(), Some("v1.1.0"));
+    assert_eq!(request.verifier.as_deref(), Some("alice"));
+    assert_eq!(request.tinygo_version.as_deref(), Some("0.38.0"));
+    assert_eq!(request.status, "queued");
+    assert!(request.git_commit.is_none() && request.exports.is_none() && request.verified_ts.is_none());
+  }
+}
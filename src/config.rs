@@ -1,5 +1,5 @@
 use serde_derive::{ Serialize, Deserialize };
-use std::{ fs, error, env::{ current_dir, set_var }, path::Path, process };
+use std::{ collections::HashMap, fs, error, env::{ current_dir, set_var }, path::Path, process };
 use env_logger;
 use log::{ info, warn };
 use rand::Rng;
@@ -9,6 +9,7 @@ use clap::Parser;
 use lazy_static::lazy_static;
 
 use crate::constants::NetworkConsts;
+use crate::types::cv::CVTinyGoLibVersions;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -36,8 +37,54 @@ pub struct CompilerConf {
   pub wasm_strip: String,
   pub wasm_tools: String,
   pub whitelist: Vec<String>,
+  /// Users allowed to call admin endpoints, requires authentication to be enabled
+  pub admins: Option<Vec<String>>,
   pub fix_permissions: Option<bool>,
+  /// Container runtime of toolchain containers, docker or podman. Defaults to docker.
+  pub runtime: Option<String>,
+  /// Podman API socket, defaults to the rootless socket of the current user
+  pub podman_socket: Option<String>,
   pub max_repo_size: Option<usize>,
+  pub workers: Option<usize>,
+  /// Maximum build attempts of a verification before it is marked as failed due to transient errors
+  pub max_attempts: Option<i32>,
+  /// Base delay in seconds before retrying a build or webhook delivery, doubled after every attempt
+  pub retry_backoff: Option<u64>,
+  /// Maximum delivery attempts of a webhook payload
+  pub webhook_max_attempts: Option<i32>,
+  pub max_log_size: Option<usize>,
+  pub archive_dir: Option<String>,
+  pub max_archive_size: Option<usize>,
+  pub allowed_git_hosts: Option<Vec<String>>,
+  pub git_hosts: Option<Vec<GitHostConf>>,
+  /// IPFS gateway for fetching deployed bytecode to diff against mismatched compiled output
+  pub ipfs_gateway: Option<String>,
+  /// Content-addressed store of verified source trees, snapshots are disabled if unset
+  pub snapshot_dir: Option<String>,
+  /// Hex encoded ed25519 secret key that signs attestations of successful verifications, attestations are disabled if unset
+  pub attestation_key: Option<String>,
+  /// TinyGo versions keyed by version, replaces the built-in version table if specified
+  pub tinygo_versions: Option<HashMap<String, CVTinyGoLibVersions>>,
+  /// Post-processing tools keyed by tool name, only configured tools may be requested
+  pub post_process_tools: Option<HashMap<String, PostProcessToolConf>>,
+  /// Interval in seconds between checks for upgraded code of verified contracts to re-verify, disabled if unset
+  pub upgrade_watch_interval: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PostProcessToolConf {
+  pub path: String,
+  /// Pinned version that the installed tool must report
+  pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GitHostConf {
+  pub host: String,
+  /// github, gitlab, gitea or forgejo
+  pub kind: String,
+  pub api_url: Option<String>,
+  pub token: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -46,7 +93,20 @@ pub struct GoCompilerConf {
   pub src_host_dir: Option<String>,
   pub output_dir: String,
   pub output_host_dir: Option<String>,
+  /// Dependencies fetched before compilation, defaults to `src_dir` suffixed with `-deps`
+  pub deps_dir: Option<String>,
+  pub deps_host_dir: Option<String>,
   pub timeout: usize,
+  /// GOPROXY used when fetching Go modules, such as a local module proxy cache
+  pub goproxy: Option<String>,
+  /// Docker network of the dependency fetch phase, the compile phase never has network access
+  pub fetch_network: Option<String>,
+  /// Memory limit of toolchain containers in bytes
+  pub memory: Option<i64>,
+  /// Number of CPUs available to toolchain containers
+  pub cpus: Option<f64>,
+  /// Maximum number of processes in toolchain containers
+  pub pids_limit: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -68,6 +128,10 @@ pub struct GiteaConf {
   pub token: String,
   pub owner: String,
   pub is_org: Option<bool>,
+  /// Interval in seconds between runs of the mirror backfill job
+  pub backfill_interval: Option<u64>,
+  /// Maximum mirror attempts of a verified contract by the backfill job
+  pub max_attempts: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -147,15 +211,44 @@ impl TomlConfig {
           wasm_strip: format!("wasm-strip"),
           wasm_tools: format!("wasm-tools"),
           whitelist: Vec::new(),
+          admins: None,
           fix_permissions: Some(false),
+          runtime: Some(format!("docker")),
+          podman_socket: None,
           max_repo_size: Some(102400),
+          workers: Some(1),
+          max_attempts: Some(5),
+          retry_backoff: Some(60),
+          webhook_max_attempts: Some(5),
+          max_log_size: Some(1048576),
+          archive_dir: Some(format!("{}/archives", current_dir().unwrap().to_str().unwrap())),
+          max_archive_size: Some(10485760),
+          allowed_git_hosts: None,
+          git_hosts: None,
+          ipfs_gateway: Some(format!("https://ipfs.io")),
+          snapshot_dir: Some(format!("{}/sources", current_dir().unwrap().to_str().unwrap())),
+          attestation_key: Some(hex::encode(rand::rng().random::<[u8; 32]>())),
+          tinygo_versions: None,
+          post_process_tools: Some(
+            HashMap::from([
+              (format!("wasm-opt"), PostProcessToolConf { path: format!("wasm-opt"), version: format!("123") }),
+            ])
+          ),
+          upgrade_watch_interval: None,
         }),
         gocompiler: GoCompilerConf {
           src_dir: format!("{}/go_compiler", current_dir().unwrap().to_str().unwrap()),
           src_host_dir: None,
           output_dir: format!("{}/artifacts", current_dir().unwrap().to_str().unwrap()),
           output_host_dir: None,
+          deps_dir: None,
+          deps_host_dir: None,
           timeout: 10,
+          goproxy: None,
+          fetch_network: None,
+          memory: Some(2147483648),
+          cpus: Some(2.0),
+          pids_limit: Some(512),
         },
         discord: None,
         gitea: None,
@@ -1,19 +1,67 @@
-use actix_web::{ get, post, web, HttpRequest, HttpResponse, Responder };
-use mongodb::bson::{ doc, DateTime };
+use actix_multipart::{
+  form::{ json::Json as MpJson, tempfile::TempFile, text::Text, MultipartForm, MultipartFormConfig },
+  MultipartError,
+};
+use actix_web::{ delete, get, post, web, HttpRequest, HttpResponse, Responder };
+use futures_util::{ stream, StreamExt };
+use mongodb::{ bson::{ doc, oid::ObjectId, DateTime }, options::{ CountOptions, FindOptions } };
+use rand::Rng;
 use serde::{ Serialize, Deserialize };
 use serde_json::{ json, Number, Value };
 use chrono::{ Utc, Duration };
-use regex::Regex;
 use hex;
 use sha2::{ Digest, Sha256 };
 use jsonwebtoken::{ Header, EncodingKey, DecodingKey, Algorithm, Validation, errors::ErrorKind };
 use utoipa::{ OpenApi, ToSchema };
+use std::{ collections::{ BTreeMap, BTreeSet, HashMap }, fs, path::Path };
+use tokio::sync::broadcast::error::RecvError;
 use crate::{
   config::config,
+  compiler::{
+    git_host::{ parse_repo_url, GitRef },
+    sbom::cyclonedx,
+    search::tokenize,
+    settings::{ apply_build_settings, BuildSettings },
+    snapshot::blob_path,
+    toolchains::tinygo_version_table,
+    webhook::is_valid_webhook_url,
+  },
+  helpers::{ archive::{ build_tar_gz, validate_archive, ArchiveKind, ArchiveLimits }, attestation },
+  mongo::is_duplicate_key,
   types::{
-    cv::{ tinygo_versions, CVContract, CVContractResult, CVStatus, CVTinyGoLibVersions },
+    cv::{
+      asc_versions,
+      rust_versions,
+      CVAscLibVersions,
+      CVAttempt,
+      CVAttemptResult,
+      CVAttestationKeyResult,
+      CVAttestationResult,
+      CVBundleMetadata,
+      CVBuildEvent,
+      CVBuildLogResult,
+      CVCodeSearchLine,
+      CVCodeSearchResult,
+      CVContract,
+      CVContractResult,
+      CVDeliveryAttemptResult,
+      CVDryRun,
+      CVDryRunResult,
+      CVQueueItem,
+      CVQueueResult,
+      CVSourceTree,
+      CVSourceTreeResult,
+      CVRustLibVersions,
+      CVSbomDependentResult,
+      CVStatus,
+      CVTinyGoLibVersions,
+      CVWebhook,
+      CVWebhookDeliveryResult,
+      CVWebhookResult,
+    },
     hive::{ DgpAtBlock, JsonRpcResp },
     server::{ Context, ErrorRes, RespErr, SuccessRes },
+    vsc::Contract,
   },
 };
 
@@ -157,50 +205,37 @@ async fn login(payload: String, ctx: web::Data<Context>) -> Result<HttpResponse,
   Ok(HttpResponse::Ok().json(json!({ "access_token": token })))
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
-struct ReqVerifyNew {
-  /// Link to GitHub repository
+/// Git repository and revision to build
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+struct GitSource {
+  /// HTTPS URL of the git repository. GitHub, GitLab, Gitea, Forgejo or any other publicly clonable host is accepted.
   repo_url: String,
-  /// Branch or tag that should be checked out. Default branch will be used if not specified.
+  /// Branch that should be checked out. Default branch will be used if none of `repo_branch`, `repo_tag` or `repo_commit` is specified.
   repo_branch: Option<String>,
-  /// Tinygo version
-  tinygo_version: String,
-  /// Tool used to strip output WASM file. Valid values: `wabt` or `wasm-tools`.
-  strip_tool: Option<String>,
-  /// Subdirectory within the repository containing the contract source code. Defaults to `contract` if not specified.
-  contract_dir: Option<String>,
-  /// Subdirectory within the repository containing the Go module (go.mod). Defaults to the repository root if not specified.
-  go_mod_dir: Option<String>,
+  /// Annotated or lightweight tag that should be checked out. Cannot be combined with `repo_branch` or `repo_commit`.
+  repo_tag: Option<String>,
+  /// Full 40 character commit hash that should be checked out. Cannot be combined with `repo_branch` or `repo_tag`.
+  repo_commit: Option<String>,
 }
 
-#[utoipa::path(
-  post,
-  path = "/verify/{address}/new",
-  context_path = "/cv-api/v1",
-  summary = "Create a new contract verification request",
-  description = "Create a new contract verification request from a GitHub repository.",
-  responses(
-    (status = 200, description = "Contract verification request created successfully", body = SuccessRes),
-    (status = 302, description = "Another contract with exact bytecode was already verified", body = ErrorRes),
-    (status = 400, description = "Failed to create contract verification request", body = ErrorRes),
-    (status = 404, description = "Contract or GitHub repository does not exist", body = ErrorRes)
-  ),
-  params(("address" = String, Path, description = "Contract address to verify")),
-  request_body = ReqVerifyNew
-)]
-#[post("/verify/{address}/new")]
-async fn verify_new(
-  req: HttpRequest,
-  path: web::Path<String>,
-  req_data: web::Json<ReqVerifyNew>,
-  ctx: web::Data<Context>
-) -> Result<HttpResponse, RespErr> {
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ReqVerifyNew {
+  #[serde(flatten)]
+  source: GitSource,
+  /// HTTPS URL notified when the verification finishes, signed with the secret of the requester's registered webhook.
+  callback_url: Option<String>,
+  #[serde(flatten)]
+  settings: BuildSettings,
+}
+
+/// Checks that the requester may verify the contract at `address` and that no identical bytecode is verified or pending.
+/// Returns the deployed contract and the requester username.
+async fn prepare_verification(req: &HttpRequest, address: &str, ctx: &Context) -> Result<(Contract, String), RespErr> {
   if ctx.compiler.is_none() {
     return Err(RespErr::CvDisabled);
   }
-  let username = verify_auth_token(&req)?;
-  let address = path.into_inner();
-  let contract = ctx.db.contracts.find_one(doc! { "id": &address }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  let username = verify_auth_token(req)?;
+  let contract = ctx.db.contracts.find_one(doc! { "id": address }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
   if contract.is_none() {
     return Err(RespErr::ContractNotFound);
   }
@@ -221,15 +256,19 @@ async fn verify_new(
     Some(similar) => {
       let is_fail = similar.status == "failed" || similar.status == "not match";
       if similar.status == CVStatus::Success.to_string() {
-        if &similar.contract_id == &address {
+        if similar.contract_id == address {
           return Err(RespErr::BadRequest { msg: String::from("Contract is already verified") });
         } else {
           return Err(RespErr::CvSimilarMatch);
         }
       } else if is_fail && similar.request_ts.to_chrono() + Duration::hours(12) > Utc::now() {
         return Err(RespErr::CvRetryLater);
-      } else if similar.status == "queued" {
-        if &similar.contract_id == &address {
+      } else if
+        similar.status == CVStatus::Queued.to_string() ||
+        similar.status == CVStatus::InProgress.to_string() ||
+        similar.status == CVStatus::Retrying.to_string()
+      {
+        if similar.contract_id == address {
           return Err(RespErr::BadRequest { msg: String::from("Contract is already queued for verification.") });
         } else {
           return Err(RespErr::BadRequest { msg: String::from("A similar contract is already queued for verification.") });
@@ -239,55 +278,50 @@ async fn verify_new(
     None => (),
   }
 
-  if &contract.runtime.value != "go" {
+  if !is_supported_lang(&contract.runtime.value) {
     return Err(RespErr::BadRequest { msg: String::from("Language is currently unsupported") });
   }
-  let repo_url = req_data.repo_url.clone();
-  if !repo_url.starts_with("https://github.com/") {
-    return Err(RespErr::CvInvalidGitHubURL);
-  }
-  let repo = repo_url.replace("https://github.com/", "");
-  let repo_id = repo.split("/").collect::<Vec<&str>>();
-  let repo_branch = req_data.repo_branch.clone().unwrap_or(format!(""));
-  if repo_id.len() != 2 || repo_id[0].len() > 39 || repo_id[1].len() > 100 {
-    return Err(RespErr::CvInvalidGitHubURL);
-  }
-  let github_user_regex: Regex = Regex::new(r"^[A-Za-z0-9-]+$").expect("Invalid regex pattern");
-  let github_repo_regex: Regex = Regex::new(r"^[A-Za-z0-9._-]+$").expect("Invalid regex pattern");
-  if !github_user_regex.is_match(repo_id[0]) || !github_repo_regex.is_match(repo_id[1]) {
-    return Err(RespErr::CvInvalidGitHubURL);
-  }
-  if repo_branch.len() > 255 {
-    return Err(RespErr::CvInvalidGitBranch);
-  }
-  match req_data.strip_tool.clone() {
-    Some(tool) => {
-      if &tool != "wabt" && &tool != "wasm-tools" {
-        return Err(RespErr::CvInvalidWasmStripTool);
-      }
-    }
-    None => (),
-  }
-  if let Some(ref dir) = req_data.contract_dir {
-    let contract_dir_regex: Regex = Regex::new(r"^[A-Za-z0-9/_.\-]+$").expect("Invalid regex pattern");
-    if dir.is_empty() || dir.contains("..") || dir.starts_with('/') || !contract_dir_regex.is_match(dir) {
-      return Err(RespErr::CvInvalidContractDir);
-    }
-  }
-  if let Some(ref dir) = req_data.go_mod_dir {
-    let go_mod_dir_regex: Regex = Regex::new(r"^[A-Za-z0-9/_.\-]+$").expect("Invalid regex pattern");
-    if dir.is_empty() || dir.contains("..") || dir.starts_with('/') || !go_mod_dir_regex.is_match(dir) {
-      return Err(RespErr::CvInvalidGoModDir);
+  Ok((contract, username))
+}
+
+fn is_supported_lang(lang: &str) -> bool {
+  lang == "go" || lang == "rust" || lang == "assemblyscript"
+}
+
+/// Validates the callback URL of a verification request. Callbacks are signed with the requester's webhook secret, hence
+/// a registered webhook is required.
+async fn validate_callback_url(ctx: &Context, username: &str, url: Option<String>) -> Result<Option<String>, RespErr> {
+  let url = match url.filter(|u| !u.is_empty()) {
+    Some(u) => u,
+    None => {
+      return Ok(None);
     }
+  };
+  if !is_valid_webhook_url(&url) {
+    return Err(RespErr::CvInvalidCallbackURL);
   }
-  if !tinygo_versions.contains_key(&req_data.tinygo_version) {
-    return Err(RespErr::CvInvalidTinyGoVersion);
+  if
+    username.is_empty() ||
+    ctx.db.cv_webhooks.find_one(doc! { "_id": username }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?.is_none()
+  {
+    return Err(RespErr::CvWebhookNotRegistered);
   }
-  let tinygo_libs = tinygo_versions.get(&req_data.tinygo_version).unwrap().clone();
-  ctx.db.cv_contracts.delete_one(doc! { "_id": &contract.code }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
-  let new_cv = CVContract {
-    contract_id: address.clone(),
-    code: contract.code.clone(),
+  Ok(Some(url))
+}
+
+/// Validates the build settings for the contract language and creates the queued verification record.
+/// Requests without build settings are built with the settings of the manifest in the source tree.
+async fn new_cv_contract(
+  ctx: &Context,
+  contract_id: &str,
+  code: &str,
+  lang: &str,
+  username: String,
+  settings: &BuildSettings
+) -> Result<CVContract, RespErr> {
+  let mut new_cv = CVContract {
+    contract_id: contract_id.to_string(),
+    code: code.to_string(),
     verifier: match username.len() {
       0 => None,
       _ => Some(username),
@@ -295,25 +329,213 @@ async fn verify_new(
     request_ts: DateTime::from_chrono(Utc::now()),
     verified_ts: None,
     status: CVStatus::Queued.to_string(),
-    repo_name: repo,
-    repo_branch: repo_branch,
+    attempts: 0,
+    started_ts: None,
+    next_attempt_ts: None,
+    completed_ts: None,
+    last_error: None,
+    repo_url: None,
+    repo_name: String::new(),
+    repo_branch: String::new(),
+    repo_tag: None,
+    repo_commit: None,
     git_commit: None,
-    tinygo_version: req_data.tinygo_version.clone(),
-    go_version: tinygo_libs.go,
-    llvm_version: tinygo_libs.llvm,
-    strip_tool: req_data.strip_tool.clone(),
-    contract_dir: req_data.contract_dir.clone(),
-    go_mod_dir: req_data.go_mod_dir.clone(),
+    tinygo_version: None,
+    go_version: None,
+    llvm_version: None,
+    tinygo_img_digest: None,
+    toolchain_img_digest: None,
+    rust_version: None,
+    cargo_profile: None,
+    cargo_features: None,
+    asc_version: None,
+    node_version: None,
+    asc_entry: None,
+    asc_options: None,
+    source_archive: None,
+    post_process: None,
+    strip_tool: None,
+    contract_dir: None,
+    go_mod_dir: None,
     exports: None,
     license: None,
-    lang: contract.runtime.value.clone(),
+    lang: lang.to_string(),
     gitea_url: None,
+    gitea_error: None,
+    gitea_attempts: 0,
+    bytecode_diff: None,
+    callback_url: None,
+    attempt_id: None,
+    build_manifest: false,
+    upgraded_from: None,
   };
+  if settings.is_empty() {
+    // resolved from the manifest in the source tree once fetched
+    new_cv.build_manifest = true;
+    return Ok(new_cv);
+  }
+  let tinygo_versions = match lang {
+    "go" => tinygo_version_table(&ctx.db, config.compiler.as_ref()).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?,
+    _ => HashMap::new(),
+  };
+  apply_build_settings(&mut new_cv, settings, &tinygo_versions, config.compiler.as_ref())?;
+  Ok(new_cv)
+}
+
+/// Validates the git repository and revision and sets them as the source of the build
+fn set_git_source(cv: &mut CVContract, source: &GitSource) -> Result<(), RespErr> {
+  let compiler_conf = config.compiler.clone().expect("compiler config should be present");
+  let repo = parse_repo_url(&source.repo_url, compiler_conf.allowed_git_hosts.as_ref()).ok_or(RespErr::CvInvalidGitURL)?;
+  let repo_branch = source.repo_branch.clone().unwrap_or_default();
+  let git_ref = GitRef::from_fields(&repo_branch, source.repo_tag.as_deref(), source.repo_commit.as_deref()).map_err(|e| {
+    RespErr::CvInvalidGitRef { msg: e }
+  })?;
+  cv.repo_url = Some(repo.url);
+  cv.repo_name = repo.path;
+  match git_ref {
+    GitRef::Branch(b) => {
+      cv.repo_branch = b;
+    }
+    GitRef::Tag(t) => {
+      cv.repo_tag = Some(t);
+    }
+    GitRef::Commit(c) => {
+      cv.repo_commit = Some(c.to_string());
+    }
+    GitRef::DefaultBranch => (),
+  }
+  Ok(())
+}
+
+/// Records the request as a new attempt, replaces the current verification of the bytecode with it and wakes up the compiler
+async fn queue_verification(ctx: &Context, mut new_cv: CVContract) -> Result<HttpResponse, RespErr> {
+  let attempt = CVAttempt::queued(&new_cv);
+  new_cv.attempt_id = Some(attempt.id);
+  ctx.db.cv_attempts.insert_one(attempt).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  ctx.db.cv_contracts.delete_one(doc! { "_id": &new_cv.code }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
   ctx.db.cv_contracts.insert_one(new_cv).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
   ctx.compiler.clone().unwrap().notify();
   Ok(HttpResponse::Ok().json(SuccessRes { success: true }))
 }
 
+#[utoipa::path(
+  post,
+  path = "/verify/{address}/new",
+  context_path = "/cv-api/v1",
+  summary = "Create a new contract verification request",
+  description = "Create a new contract verification request from a public git repository. Go, Rust and AssemblyScript contracts are supported, each requiring its own toolchain version field. If no build settings are specified, they are read from `magi-contract.toml` in the repository root and validated against the same rules.",
+  responses(
+    (status = 200, description = "Contract verification request created successfully", body = SuccessRes),
+    (status = 302, description = "Another contract with exact bytecode was already verified", body = ErrorRes),
+    (status = 400, description = "Failed to create contract verification request", body = ErrorRes),
+    (status = 404, description = "Contract or git repository does not exist", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address to verify")),
+  request_body = ReqVerifyNew
+)]
+#[post("/verify/{address}/new")]
+async fn verify_new(
+  req: HttpRequest,
+  path: web::Path<String>,
+  req_data: web::Json<ReqVerifyNew>,
+  ctx: web::Data<Context>
+) -> Result<HttpResponse, RespErr> {
+  let address = path.into_inner();
+  let (contract, username) = prepare_verification(&req, &address, &ctx).await?;
+  let callback_url = validate_callback_url(&ctx, &username, req_data.callback_url.clone()).await?;
+  let mut new_cv = new_cv_contract(&ctx, &contract.id, &contract.code, &contract.runtime.value, username, &req_data.settings).await?;
+  set_git_source(&mut new_cv, &req_data.source)?;
+  new_cv.callback_url = callback_url;
+  queue_verification(&ctx, new_cv).await
+}
+
+/// Size limit of an uploaded source archive in bytes, bounded by the maximum extracted repository size
+fn max_upload_size() -> usize {
+  let compiler_conf = config.compiler.as_ref();
+  let max_archive_size = compiler_conf.and_then(|c| c.max_archive_size).unwrap_or(10485760);
+  max_archive_size.min(compiler_conf.and_then(|c| c.max_repo_size).unwrap_or(102400) * 1024)
+}
+
+/// Multipart form limits of source archive uploads. The upload is aborted as soon as it exceeds the archive size limit
+/// rather than after it has been received in full.
+pub fn upload_form_config() -> MultipartFormConfig {
+  MultipartFormConfig::default()
+    // the other fields are small JSON and text values
+    .total_limit(max_upload_size() + 65536)
+    .error_handler(|e, _| {
+      match e {
+        MultipartError::Payload(actix_web::error::PayloadError::Overflow) => RespErr::CvArchiveTooLarge.into(),
+        e => e.into(),
+      }
+    })
+}
+
+#[derive(MultipartForm)]
+struct ReqVerifyUpload {
+  archive: TempFile,
+  settings: MpJson<BuildSettings>,
+  callback_url: Option<Text<String>>,
+}
+
+#[derive(ToSchema)]
+#[allow(dead_code)]
+struct ReqVerifyUploadForm {
+  /// Source archive in `.tar.gz` or `.zip` format. A single top-level directory is stripped when extracting.
+  #[schema(value_type = String, format = Binary)]
+  archive: Vec<u8>,
+  /// Build settings as JSON
+  settings: BuildSettings,
+  /// HTTPS URL notified when the verification finishes, signed with the secret of the requester's registered webhook.
+  callback_url: Option<String>,
+}
+
+#[utoipa::path(
+  post,
+  path = "/verify/{address}/upload",
+  context_path = "/cv-api/v1",
+  summary = "Create a new contract verification request from a source archive",
+  description = "Create a new contract verification request from an uploaded `.tar.gz` or `.zip` source archive. The archive is kept as the source of record of the verification.",
+  responses(
+    (status = 200, description = "Contract verification request created successfully", body = SuccessRes),
+    (status = 302, description = "Another contract with exact bytecode was already verified", body = ErrorRes),
+    (status = 400, description = "Failed to create contract verification request", body = ErrorRes),
+    (status = 404, description = "Contract does not exist", body = ErrorRes),
+    (status = 413, description = "Source archive is too large", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address to verify")),
+  request_body(content = ReqVerifyUploadForm, content_type = "multipart/form-data")
+)]
+#[post("/verify/{address}/upload")]
+async fn verify_upload(
+  req: HttpRequest,
+  path: web::Path<String>,
+  MultipartForm(form): MultipartForm<ReqVerifyUpload>,
+  ctx: web::Data<Context>
+) -> Result<HttpResponse, RespErr> {
+  let address = path.into_inner();
+  let (contract, username) = prepare_verification(&req, &address, &ctx).await?;
+  let compiler_conf = config.compiler.clone().expect("compiler config should be present");
+  let archive_dir = compiler_conf.archive_dir.clone().ok_or(RespErr::BadRequest {
+    msg: String::from("Source archive uploads are disabled"),
+  })?;
+  if form.archive.size > max_upload_size() {
+    return Err(RespErr::CvArchiveTooLarge);
+  }
+  let data = fs::read(form.archive.file.path()).map_err(|e| RespErr::InternalErr { msg: e.to_string() })?;
+  let kind = ArchiveKind::detect(&data).ok_or(RespErr::CvInvalidArchive { msg: String::from("Unsupported archive format") })?;
+  validate_archive(&data, kind, ArchiveLimits::from_conf(&compiler_conf)).map_err(|e| RespErr::CvInvalidArchive { msg: e })?;
+  let callback_url = validate_callback_url(&ctx, &username, form.callback_url.map(|u| u.into_inner())).await?;
+  let mut new_cv = new_cv_contract(&ctx, &contract.id, &contract.code, &contract.runtime.value, username, &form.settings).await?;
+  new_cv.callback_url = callback_url;
+  let mut hasher = Sha256::new();
+  hasher.update(&data);
+  let archive_name = format!("{}.{}", hex::encode(&hasher.finalize()[..]), kind.extension());
+  fs::create_dir_all(&archive_dir).map_err(|e| RespErr::InternalErr { msg: e.to_string() })?;
+  fs::write(Path::new(&archive_dir).join(&archive_name), &data).map_err(|e| RespErr::InternalErr { msg: e.to_string() })?;
+  new_cv.source_archive = Some(archive_name);
+  queue_verification(&ctx, new_cv).await
+}
+
 #[utoipa::path(
   get,
   path = "/contract/{address}",
@@ -352,12 +574,35 @@ async fn contract_info(path: web::Path<String>, ctx: web::Data<Context>) -> Resu
           request_ts: similar.request_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
           verified_ts: similar.verified_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
           status: similar.status.clone(),
+          attempts: similar.attempts,
+          started_ts: similar.started_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
+          next_attempt_ts: similar.next_attempt_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
+          completed_ts: similar.completed_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
+          last_error: similar.last_error,
+          repo_url: match (similar.repo_url, similar.repo_name.is_empty()) {
+            (Some(url), _) => url,
+            (None, false) => format!("https://github.com/{}", similar.repo_name),
+            (None, true) => String::new(),
+          },
           repo_name: similar.repo_name,
           repo_branch: similar.repo_branch,
+          repo_tag: similar.repo_tag,
+          repo_commit: similar.repo_commit,
           git_commit: similar.git_commit,
           tinygo_version: similar.tinygo_version,
           go_version: similar.go_version,
           llvm_version: similar.llvm_version,
+          tinygo_img_digest: similar.tinygo_img_digest,
+          toolchain_img_digest: similar.toolchain_img_digest,
+          rust_version: similar.rust_version,
+          cargo_profile: similar.cargo_profile,
+          cargo_features: similar.cargo_features,
+          asc_version: similar.asc_version,
+          node_version: similar.node_version,
+          asc_entry: similar.asc_entry,
+          asc_options: similar.asc_options,
+          source_archive: similar.source_archive,
+          post_process: similar.post_process,
           strip_tool: similar.strip_tool,
           contract_dir: similar.contract_dir,
           go_mod_dir: similar.go_mod_dir,
@@ -365,6 +610,9 @@ async fn contract_info(path: web::Path<String>, ctx: web::Data<Context>) -> Resu
           license: similar.license,
           lang: similar.lang.clone(),
           gitea_url: similar.gitea_url,
+          gitea_error: similar.gitea_error,
+          bytecode_diff: similar.bytecode_diff,
+          upgraded_from: similar.upgraded_from,
         })
       );
     }
@@ -373,6 +621,1074 @@ async fn contract_info(path: web::Path<String>, ctx: web::Data<Context>) -> Resu
   Err(RespErr::ContractNotFound)
 }
 
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/logs",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve build logs of a contract's latest verification attempt",
+  responses(
+    (status = 200, description = "Build logs of the latest verification attempt", body = CVBuildLogResult),
+    (status = 404, description = "Contract or build logs not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/logs")]
+async fn contract_logs(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let deployed_contract = match
+    ctx.db.contracts.find_one(doc! { "id": &addr }).await.map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+  {
+    Some(c) => c,
+    None => {
+      return Err(RespErr::ContractNotFound);
+    }
+  };
+  let build_log = ctx.db.cv_logs
+    .find_one(doc! { "_id": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvLogsNotFound)?;
+  Ok(
+    HttpResponse::Ok().json(CVBuildLogResult {
+      address: addr,
+      code: build_log.code,
+      request_ts: build_log.request_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+      truncated: build_log.truncated,
+      logs: build_log.logs,
+    })
+  )
+}
+
+/// Maximum number of attempts listed per contract
+const ATTEMPTS_LIST_LIMIT: i64 = 100;
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/attempts",
+  context_path = "/cv-api/v1",
+  summary = "List verification attempts of a contract",
+  description = "Every verification request of the contract bytecode with its settings and outcome, newest first. Requests made through other contracts with identical bytecode are included.",
+  responses(
+    (status = 200, description = "Verification attempts", body = [CVAttemptResult]),
+    (status = 404, description = "Contract not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/attempts")]
+async fn contract_attempts(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": &addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  let opt = FindOptions::builder().sort(doc! { "request_ts": -1 }).limit(ATTEMPTS_LIST_LIMIT).build();
+  let mut cursor = ctx.db.cv_attempts
+    .find(doc! { "code": &deployed_contract.code })
+    .with_options(opt).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  let mut results = Vec::new();
+  while let Some(attempt) = cursor.next().await {
+    let attempt = attempt.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    let id = attempt.id.to_hex();
+    results.push(CVAttemptResult {
+      logs_url: match attempt.has_logs {
+        true => Some(format!("/cv-api/v1/contract/{}/attempts/{}/logs", addr, id)),
+        false => None,
+      },
+      id,
+      address: attempt.contract_id,
+      code: attempt.code,
+      requester: attempt.requester,
+      request_ts: attempt.request_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+      settings: attempt.settings,
+      status: attempt.status,
+      attempts: attempt.attempts,
+      git_commit: attempt.git_commit,
+      output_cid: attempt.output_cid,
+      last_error: attempt.last_error,
+      completed_ts: attempt.completed_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
+      upgraded_from: attempt.upgraded_from,
+    });
+  }
+  Ok(HttpResponse::Ok().json(results))
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/attempts/{id}/logs",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve build logs of a verification attempt",
+  responses(
+    (status = 200, description = "Build logs of the latest build of the attempt", body = CVBuildLogResult),
+    (status = 404, description = "Contract, attempt or build logs not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"), ("id" = String, Path, description = "Attempt ID"))
+)]
+#[get("/contract/{address}/attempts/{id}/logs")]
+async fn attempt_logs(path: web::Path<(String, String)>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let (addr, id) = path.into_inner();
+  let id = ObjectId::parse_str(&id).map_err(|_| RespErr::CvLogsNotFound)?;
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": &addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  let attempt = ctx.db.cv_attempts
+    .find_one(doc! { "_id": id, "code": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvLogsNotFound)?;
+  let build_log = ctx.db.cv_attempt_logs
+    .find_one(doc! { "_id": attempt.id }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvLogsNotFound)?;
+  Ok(
+    HttpResponse::Ok().json(CVBuildLogResult {
+      address: addr,
+      code: attempt.code,
+      request_ts: attempt.request_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+      truncated: build_log.truncated,
+      logs: build_log.logs,
+    })
+  )
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/attestation",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve the signed attestation of a contract verification",
+  description = "Attestation of a successful verification, signed with the server key over the exact bytes of `payload`. Verify it against the public key from `/attestation/key` obtained out of band.",
+  responses(
+    (status = 200, description = "Signed verification attestation", body = CVAttestationResult),
+    (status = 404, description = "Contract or attestation not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/attestation")]
+async fn contract_attestation(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": &addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  let attestation = ctx.db.cv_attestations
+    .find_one(doc! { "_id": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvAttestationNotFound)?;
+  Ok(
+    HttpResponse::Ok().json(CVAttestationResult {
+      payload: attestation.payload,
+      signature: attestation.signature,
+      public_key: attestation.public_key,
+      algorithm: attestation.algorithm,
+    })
+  )
+}
+
+#[utoipa::path(
+  get,
+  path = "/attestation/key",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve the public key that signs verification attestations",
+  responses(
+    (status = 200, description = "Attestation public key", body = CVAttestationKeyResult),
+    (status = 404, description = "Attestations are disabled", body = ErrorRes)
+  )
+)]
+#[get("/attestation/key")]
+async fn attestation_key() -> Result<HttpResponse, RespErr> {
+  let key = config.compiler
+    .as_ref()
+    .and_then(|c| c.attestation_key.clone())
+    .ok_or(RespErr::CvAttestationNotFound)?;
+  let key = attestation::signing_key(&key).map_err(|e| RespErr::InternalErr { msg: e })?;
+  Ok(
+    HttpResponse::Ok().json(CVAttestationKeyResult {
+      public_key: attestation::public_key(&key),
+      algorithm: String::from(attestation::ALGORITHM),
+    })
+  )
+}
+
+/// Interval in seconds between keep-alive comments of an idle event stream
+const EVENTS_KEEPALIVE: u64 = 15;
+
+fn sse_frame(event: &CVBuildEvent) -> web::Bytes {
+  let data = serde_json::to_string(event).expect("Should serialize to json correctly");
+  web::Bytes::from(format!("event: {}\ndata: {}\n\n", event.name(), data))
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/events",
+  context_path = "/cv-api/v1",
+  summary = "Stream live build progress of a contract verification",
+  description = "Server-Sent Events stream of the verification in progress. A `state` event is sent for the current status followed by every build state transition (cloning, extracting, pulling, fetching, compiling, postprocessing, stripping, hashing, diffing, mirroring, snapshotting), `log` events carry build log lines including live container output. The stream ends with a `status` event carrying the outcome of the build attempt, which is sent immediately if the verification is not pending.",
+  responses(
+    (status = 200, description = "Event stream", content_type = "text/event-stream", body = CVBuildEvent),
+    (status = 404, description = "Contract or verification not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/events")]
+async fn contract_events(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let compiler = ctx.compiler.clone().ok_or(RespErr::CvDisabled)?;
+  let addr = path.into_inner();
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": &addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  // subscribe before reading the status so that the final status of a build finishing in between is not missed
+  let rx = compiler.events().subscribe(&deployed_contract.code);
+  let cv = ctx.db.cv_contracts
+    .find_one(doc! { "_id": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  let pending = [CVStatus::Queued, CVStatus::InProgress, CVStatus::Retrying].iter().any(|s| s.to_string() == cv.status);
+  let (first, rx) = match pending {
+    true => (CVBuildEvent::State { state: cv.status }, Some(rx)),
+    false => (CVBuildEvent::Status { status: cv.status }, None),
+  };
+  let live = stream::unfold(rx, |rx| async move {
+    let mut rx = rx?;
+    loop {
+      match tokio::time::timeout(std::time::Duration::from_secs(EVENTS_KEEPALIVE), rx.recv()).await {
+        Err(_) => {
+          return Some((Ok(web::Bytes::from_static(b": keep-alive\n\n")), Some(rx)));
+        }
+        Ok(Ok(event)) => {
+          let next = match event {
+            CVBuildEvent::Status { .. } => None,
+            _ => Some(rx),
+          };
+          return Some((Ok::<_, actix_web::Error>(sse_frame(&event)), next));
+        }
+        Ok(Err(RecvError::Lagged(_))) => {
+          continue;
+        }
+        Ok(Err(RecvError::Closed)) => {
+          return None;
+        }
+      }
+    }
+  });
+  Ok(
+    HttpResponse::Ok()
+      .content_type("text/event-stream")
+      .insert_header(("Cache-Control", "no-cache"))
+      .insert_header(("X-Accel-Buffering", "no"))
+      .streaming(stream::once(async move { Ok(sse_frame(&first)) }).chain(live))
+  )
+}
+
+/// Verified source tree of a deployed contract
+async fn source_tree(addr: &str, ctx: &Context) -> Result<CVSourceTree, RespErr> {
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  ctx.db.cv_sources
+    .find_one(doc! { "_id": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvSourceNotFound)
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/files",
+  context_path = "/cv-api/v1",
+  summary = "List source files of a verified contract",
+  responses(
+    (status = 200, description = "File tree of the verified source code", body = CVSourceTreeResult),
+    (status = 404, description = "Contract or verified source files not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/files")]
+async fn contract_files(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let tree = source_tree(&addr, &ctx).await?;
+  Ok(
+    HttpResponse::Ok().json(CVSourceTreeResult {
+      address: addr,
+      code: tree.code,
+      snapshot_ts: tree.snapshot_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+      files: tree.files,
+    })
+  )
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/files/{file_path}",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve a source file of a verified contract",
+  description = "Returns the raw file contents. Text files are served as `text/plain`, other files as `application/octet-stream`.",
+  responses(
+    (status = 200, description = "Source file contents", body = String),
+    (status = 404, description = "Contract or source file not found", body = ErrorRes)
+  ),
+  params(
+    ("address" = String, Path, description = "Contract address"),
+    ("file_path" = String, Path, description = "File path relative to the source root as listed in the file tree")
+  )
+)]
+#[get("/contract/{address}/files/{file_path:.*}")]
+async fn contract_file(path: web::Path<(String, String)>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let (addr, file_path) = path.into_inner();
+  let store_dir = config.compiler
+    .as_ref()
+    .and_then(|c| c.snapshot_dir.clone())
+    .ok_or(RespErr::CvSourceNotFound)?;
+  let tree = source_tree(&addr, &ctx).await?;
+  let file = tree.files
+    .iter()
+    .find(|f| f.path == file_path)
+    .ok_or(RespErr::CvSourceFileNotFound)?;
+  let data = fs::read(blob_path(&store_dir, &file.sha256)).map_err(|e| RespErr::InternalErr { msg: e.to_string() })?;
+  let content_type = match std::str::from_utf8(&data) {
+    Ok(_) => "text/plain; charset=utf-8",
+    Err(_) => "application/octet-stream",
+  };
+  Ok(HttpResponse::Ok().content_type(content_type).body(data))
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/bundle",
+  context_path = "/cv-api/v1",
+  summary = "Download the verification bundle of a verified contract",
+  description = "Returns a `.tar.gz` archive with a single `<code>` directory containing `metadata.json` with the toolchain, build settings, commit and exports, the exact source tree used under `source/` and the compiled `build.wasm`. This is sufficient to reproduce the build offline.",
+  responses(
+    (status = 200, description = "Verification bundle", content_type = "application/gzip", body = Vec<u8>),
+    (status = 404, description = "Contract or verified source files not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/bundle")]
+async fn contract_bundle(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let store_dir = config.compiler
+    .as_ref()
+    .and_then(|c| c.snapshot_dir.clone())
+    .ok_or(RespErr::CvSourceNotFound)?;
+  let tree = source_tree(&addr, &ctx).await?;
+  let cv = ctx.db.cv_contracts
+    .find_one(doc! { "_id": &tree.code, "status": CVStatus::Success.to_string() }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvSourceNotFound)?;
+  let read_blob = |sha256: &str| fs::read(blob_path(&store_dir, sha256)).map_err(|e| RespErr::InternalErr { msg: e.to_string() });
+  let mut files = Vec::with_capacity(tree.files.len() + 2);
+  for file in tree.files.iter() {
+    files.push((format!("{}/source/{}", cv.code, file.path), read_blob(&file.sha256)?));
+  }
+  if let Some(sha256) = &tree.wasm_sha256 {
+    files.push((format!("{}/build.wasm", cv.code), read_blob(sha256)?));
+  }
+  let metadata = CVBundleMetadata {
+    address: addr,
+    code: cv.code.clone(),
+    lang: cv.lang,
+    repo_url: cv.repo_url,
+    repo_branch: Some(cv.repo_branch).filter(|b| !b.is_empty()),
+    repo_tag: cv.repo_tag,
+    git_commit: cv.git_commit,
+    source_archive: cv.source_archive,
+    tinygo_version: cv.tinygo_version,
+    go_version: cv.go_version,
+    llvm_version: cv.llvm_version,
+    tinygo_img_digest: cv.tinygo_img_digest,
+    toolchain_img_digest: cv.toolchain_img_digest,
+    rust_version: cv.rust_version,
+    cargo_profile: cv.cargo_profile,
+    cargo_features: cv.cargo_features,
+    asc_version: cv.asc_version,
+    node_version: cv.node_version,
+    asc_entry: cv.asc_entry,
+    asc_options: cv.asc_options,
+    contract_dir: cv.contract_dir,
+    go_mod_dir: cv.go_mod_dir,
+    post_process: cv.post_process,
+    strip_tool: cv.strip_tool,
+    exports: cv.exports,
+    license: cv.license,
+    verified_ts: cv.verified_ts.map(|t| t.to_chrono().format(TIMESTAMP_FORMAT).to_string()),
+    files: tree.files,
+    wasm_sha256: tree.wasm_sha256,
+  };
+  let metadata_json = serde_json::to_vec_pretty(&metadata).map_err(|e| RespErr::InternalErr { msg: e.to_string() })?;
+  files.insert(0, (format!("{}/metadata.json", cv.code), metadata_json));
+  let mtime = tree.snapshot_ts.timestamp_millis().max(0) / 1000;
+  let bundle = build_tar_gz(&files, mtime as u64).map_err(|e| RespErr::InternalErr { msg: e })?;
+  Ok(
+    HttpResponse::Ok()
+      .content_type("application/gzip")
+      .insert_header(("Content-Disposition", format!("attachment; filename=\"{}.tar.gz\"", cv.code)))
+      .body(bundle)
+  )
+}
+
+#[utoipa::path(
+  get,
+  path = "/contract/{address}/sbom",
+  context_path = "/cv-api/v1",
+  summary = "Retrieve the dependency SBOM of a verified contract",
+  description = "CycloneDX 1.5 JSON document listing the Go modules required by the verified source tree, with their versions, replacements and go.sum checksums.",
+  responses(
+    (status = 200, description = "CycloneDX SBOM", content_type = "application/vnd.cyclonedx+json", body = Object),
+    (status = 404, description = "Contract or SBOM not found", body = ErrorRes)
+  ),
+  params(("address" = String, Path, description = "Contract address"))
+)]
+#[get("/contract/{address}/sbom")]
+async fn contract_sbom(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let addr = path.into_inner();
+  let deployed_contract = ctx.db.contracts
+    .find_one(doc! { "id": &addr }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::ContractNotFound)?;
+  let sbom = ctx.db.cv_sboms
+    .find_one(doc! { "_id": &deployed_contract.code }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvSbomNotFound)?;
+  Ok(HttpResponse::Ok().content_type("application/vnd.cyclonedx+json").json(cyclonedx(&sbom)))
+}
+
+#[derive(Deserialize)]
+struct SbomDependentsOpts {
+  module: String,
+  version: Option<String>,
+}
+
+#[utoipa::path(
+  get,
+  path = "/sbom/dependents",
+  context_path = "/cv-api/v1",
+  summary = "List verified contracts depending on a Go module",
+  description = "Matches the module path and version against both required modules and their replacements, including indirect dependencies.",
+  responses((status = 200, description = "Verified contracts depending on the module", body = Vec<CVSbomDependentResult>)),
+  params(
+    ("module" = String, Query, description = "Go module path"),
+    ("version" = Option<String>, Query, description = "Module version, any version if not specified")
+  )
+)]
+#[get("/sbom/dependents")]
+async fn sbom_dependents(params: web::Query<SbomDependentsOpts>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let mut required = doc! { "path": &params.module };
+  let mut replacement = doc! { "replace_path": &params.module };
+  if let Some(v) = &params.version {
+    required.insert("version", v);
+    replacement.insert("replace_version", v);
+  }
+  let mut cursor = ctx.db.cv_sboms
+    .find(doc! { "components": { "$elemMatch": { "$or": [&required, &replacement] } } }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  let is_match = |path: &str, version: &str| path == params.module && params.version.as_ref().is_none_or(|v| v == version);
+  let mut results = Vec::new();
+  while let Some(sbom) = cursor.next().await {
+    let sbom = sbom.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    for dependency in sbom.components {
+      let replaced = match (&dependency.replace_path, &dependency.replace_version) {
+        (Some(p), v) => is_match(p, v.as_deref().unwrap_or_default()),
+        _ => false,
+      };
+      if is_match(&dependency.path, &dependency.version) || replaced {
+        results.push(CVSbomDependentResult {
+          address: sbom.contract_id.clone(),
+          code: sbom.code.clone(),
+          module: sbom.module.clone(),
+          dependency,
+        });
+      }
+    }
+  }
+  Ok(HttpResponse::Ok().json(results))
+}
+
+/// Maximum number of terms of a source search query
+const SEARCH_MAX_TERMS: usize = 8;
+/// Maximum number of matching lines returned per file
+const SEARCH_MAX_LINES: usize = 10;
+/// Maximum number of characters of a returned line
+const SEARCH_MAX_LINE_LEN: usize = 200;
+/// Maximum number of postings read per search term, i.e. verified bytecodes searched
+const SEARCH_MAX_POSTINGS: u64 = 1000;
+
+#[derive(Deserialize)]
+struct SearchCodeOpts {
+  q: String,
+  limit: Option<usize>,
+}
+
+struct SearchHit {
+  contract_id: String,
+  sha256: String,
+  lines: BTreeSet<u32>,
+}
+
+#[utoipa::path(
+  get,
+  path = "/search/code",
+  context_path = "/cv-api/v1",
+  summary = "Search verified contract sources",
+  description = "Finds source files of verified contracts with lines containing every search term. Terms are identifiers and words of at least 2 characters matched case-insensitively, e.g. `sdk.Transfer` matches lines containing both `sdk` and `transfer`. At most 1000 verified bytecodes containing the rarest term are searched.",
+  responses(
+    (status = 200, description = "Matching source files", body = Vec<CVCodeSearchResult>),
+    (status = 400, description = "Invalid search query", body = ErrorRes),
+    (status = 404, description = "Verified source files are not preserved", body = ErrorRes)
+  ),
+  params(
+    ("q" = String, Query, description = "Search terms"),
+    ("limit" = Option<usize>, Query, description = "Maximum number of files returned, 20 by default and at most 100")
+  )
+)]
+#[get("/search/code")]
+async fn search_code(params: web::Query<SearchCodeOpts>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let mut terms = tokenize(&params.q);
+  terms.sort();
+  terms.dedup();
+  if terms.is_empty() {
+    return Err(RespErr::BadRequest { msg: String::from("Search query must contain at least one word of 2 or more characters") });
+  } else if terms.len() > SEARCH_MAX_TERMS {
+    return Err(RespErr::BadRequest { msg: format!("At most {} search terms are allowed", SEARCH_MAX_TERMS) });
+  }
+  let limit = params.limit.unwrap_or(20).clamp(1, 100);
+  let store_dir = config.compiler
+    .as_ref()
+    .and_then(|c| c.snapshot_dir.clone())
+    .ok_or(RespErr::CvSourceNotFound)?;
+  // the rarest term yields the fewest candidates for the other terms to be matched against
+  let mut counts = Vec::with_capacity(terms.len());
+  for term in terms {
+    let count_opt = CountOptions::builder().limit(SEARCH_MAX_POSTINGS).build();
+    let count = ctx.db.cv_search
+      .count_documents(doc! { "token": &term })
+      .with_options(count_opt).await
+      .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    counts.push((count, term));
+  }
+  counts.sort();
+  // files keyed by bytecode and path, narrowed down to the lines containing every term so far
+  let mut hits: Option<BTreeMap<(String, String), SearchHit>> = None;
+  for (_, term) in counts {
+    let mut filter = doc! { "token": &term };
+    if let Some(h) = &hits {
+      let codes: BTreeSet<String> = h.keys().map(|(code, _)| code.clone()).collect();
+      filter.insert("code", doc! { "$in": codes.into_iter().collect::<Vec<String>>() });
+    }
+    let opt = FindOptions::builder()
+      .sort(doc! { "code": 1 })
+      .limit(SEARCH_MAX_POSTINGS as i64)
+      .build();
+    let mut cursor = ctx.db.cv_search
+      .find(filter)
+      .with_options(opt).await
+      .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    let mut term_hits = BTreeMap::new();
+    while let Some(posting) = cursor.next().await {
+      let posting = posting.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+      for file in posting.files {
+        let hit = SearchHit { contract_id: posting.contract_id.clone(), sha256: file.sha256, lines: file.lines.into_iter().collect() };
+        term_hits.insert((posting.code.clone(), file.path), hit);
+      }
+    }
+    let narrowed: BTreeMap<(String, String), SearchHit> = match hits {
+      None => term_hits,
+      Some(prev) =>
+        prev
+          .into_iter()
+          .filter_map(|(key, mut hit)| {
+            let other = term_hits.get(&key)?;
+            hit.lines.retain(|l| other.lines.contains(l));
+            Some((key, hit)).filter(|(_, h)| !h.lines.is_empty())
+          })
+          .collect(),
+    };
+    let done = narrowed.is_empty();
+    hits = Some(narrowed);
+    if done {
+      break;
+    }
+  }
+  let mut results = Vec::new();
+  for ((code, path), hit) in hits.unwrap_or_default().into_iter().take(limit) {
+    let text = fs
+      ::read(blob_path(&store_dir, &hit.sha256))
+      .map(|d| String::from_utf8_lossy(&d).into_owned())
+      .unwrap_or_default();
+    let source_lines: Vec<&str> = text.lines().collect();
+    let lines = hit.lines
+      .iter()
+      .take(SEARCH_MAX_LINES)
+      .map(|l| CVCodeSearchLine {
+        line: *l,
+        text: source_lines
+          .get((*l as usize) - 1)
+          .map(|t| t.trim().chars().take(SEARCH_MAX_LINE_LEN).collect())
+          .unwrap_or_default(),
+      })
+      .collect();
+    results.push(CVCodeSearchResult { address: hit.contract_id, code, path, lines });
+  }
+  Ok(HttpResponse::Ok().json(results))
+}
+
+/// Number of recently completed verifications listed and used to estimate build durations
+const QUEUE_RECENT_LIMIT: i64 = 20;
+/// Maximum number of queued verifications listed
+const QUEUE_LIST_LIMIT: i64 = 100;
+
+async fn find_cv_contracts(
+  ctx: &Context,
+  filter: mongodb::bson::Document,
+  sort: mongodb::bson::Document,
+  limit: i64
+) -> Result<Vec<CVContract>, RespErr> {
+  let opt = FindOptions::builder().sort(sort).limit(limit).build();
+  let mut cursor = ctx.db.cv_contracts
+    .find(filter)
+    .with_options(opt).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  let mut results = Vec::new();
+  while let Some(doc) = cursor.next().await {
+    results.push(doc.map_err(|e| RespErr::DbErr { msg: e.to_string() })?);
+  }
+  Ok(results)
+}
+
+fn queue_item(c: &CVContract) -> CVQueueItem {
+  let fmt = |t: DateTime| t.to_chrono().format(TIMESTAMP_FORMAT).to_string();
+  CVQueueItem {
+    address: c.contract_id.clone(),
+    code: c.code.clone(),
+    lang: c.lang.clone(),
+    status: c.status.clone(),
+    request_ts: fmt(c.request_ts),
+    position: None,
+    attempts: c.attempts,
+    started_ts: c.started_ts.map(fmt),
+    next_attempt_ts: c.next_attempt_ts.map(fmt),
+    eta: None,
+    completed_ts: c.completed_ts.map(fmt),
+  }
+}
+
+#[utoipa::path(
+  get,
+  path = "/queue",
+  context_path = "/cv-api/v1",
+  summary = "Inspect the contract verification queue",
+  description = "Lists verifications in progress, queued and retrying, along with recently completed ones. Estimated start times are based on the average duration of recent builds.",
+  responses((status = 200, description = "Contract verification queue", body = CVQueueResult))
+)]
+#[get("/queue")]
+async fn verification_queue(ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let workers = config.compiler
+    .as_ref()
+    .and_then(|c| c.workers)
+    .unwrap_or(1)
+    .max(1);
+  let in_progress = find_cv_contracts(
+    &ctx,
+    doc! { "status": CVStatus::InProgress.to_string() },
+    doc! { "started_ts": 1 },
+    workers as i64
+  ).await?;
+  let queued = find_cv_contracts(
+    &ctx,
+    doc! { "status": CVStatus::Queued.to_string() },
+    doc! { "request_ts": 1 },
+    QUEUE_LIST_LIMIT
+  ).await?;
+  let retrying = find_cv_contracts(
+    &ctx,
+    doc! { "status": CVStatus::Retrying.to_string() },
+    doc! { "next_attempt_ts": 1 },
+    QUEUE_LIST_LIMIT
+  ).await?;
+  let recent = find_cv_contracts(
+    &ctx,
+    doc! { "completed_ts": { "$exists": true } },
+    doc! { "completed_ts": -1 },
+    QUEUE_RECENT_LIMIT
+  ).await?;
+
+  let durations: Vec<i64> = recent
+    .iter()
+    .filter_map(|c| Some((c.completed_ts?.timestamp_millis() - c.started_ts?.timestamp_millis()) / 1000))
+    .filter(|d| *d >= 0)
+    .collect();
+  let avg_build_duration = match durations.len() {
+    0 => None,
+    n => Some((durations.iter().sum::<i64>() as f64) / (n as f64)),
+  };
+  let fmt_ts = |ms: i64| DateTime::from_millis(ms).to_chrono().format(TIMESTAMP_FORMAT).to_string();
+  let now = Utc::now().timestamp_millis();
+
+  let mut in_progress_items: Vec<CVQueueItem> = in_progress.iter().map(queue_item).collect();
+  let mut queued_items: Vec<CVQueueItem> = queued.iter().map(queue_item).collect();
+  for (i, item) in queued_items.iter_mut().enumerate() {
+    item.position = Some(i + 1);
+  }
+  if let Some(avg) = avg_build_duration {
+    let avg_ms = (avg * 1000.0) as i64;
+    // simulate the workers picking up queued contracts as soon as they finish their current build
+    let mut worker_free: Vec<i64> = vec![now; workers];
+    for (i, (item, c)) in in_progress_items.iter_mut().zip(in_progress.iter()).enumerate() {
+      let started = c.started_ts.map(|t| t.timestamp_millis()).unwrap_or(now);
+      let finish = (started + avg_ms).max(now);
+      item.eta = Some(fmt_ts(finish));
+      worker_free[i % workers] = finish;
+    }
+    for item in queued_items.iter_mut() {
+      let (idx, start) = worker_free
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, t)| **t)
+        .map(|(i, t)| (i, *t))
+        .unwrap();
+      item.eta = Some(fmt_ts(start));
+      worker_free[idx] = start + avg_ms;
+    }
+  }
+
+  Ok(
+    HttpResponse::Ok().json(CVQueueResult {
+      workers,
+      avg_build_duration,
+      in_progress: in_progress_items,
+      queued: queued_items,
+      retrying: retrying.iter().map(queue_item).collect(),
+      recent: recent.iter().map(queue_item).collect(),
+    })
+  )
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ReqCompile {
+  /// Contract language. Valid values: `go`, `rust` or `assemblyscript`.
+  lang: String,
+  #[serde(flatten)]
+  source: GitSource,
+  #[serde(flatten)]
+  settings: BuildSettings,
+}
+
+#[derive(Serialize, ToSchema)]
+struct ResCompile {
+  /// Dry run ID to lookup the result with
+  id: String,
+}
+
+#[utoipa::path(
+  post,
+  path = "/compile",
+  context_path = "/cv-api/v1",
+  summary = "Create a dry run compilation",
+  description = "Compile a contract from a public git repository with the same settings as a verification request, without matching the output against a deployed contract. The result includes the raw CID that the contract code will have once deployed. Dry runs are built only while no verification is waiting, one pending dry run is allowed per user.",
+  responses(
+    (status = 200, description = "Dry run queued successfully", body = ResCompile),
+    (status = 400, description = "Failed to create dry run", body = ErrorRes)
+  ),
+  request_body = ReqCompile
+)]
+#[post("/compile")]
+async fn compile_new(req: HttpRequest, req_data: web::Json<ReqCompile>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  if ctx.compiler.is_none() {
+    return Err(RespErr::CvDisabled);
+  }
+  let username = verify_auth_token(&req)?;
+  if config.auth.enabled {
+    let whitelist = config.compiler.clone().expect("compiler config should be present").whitelist.clone();
+    if !whitelist.is_empty() && !whitelist.contains(&username) {
+      return Err(RespErr::CvNotWhitelisted);
+    }
+  }
+  if !is_supported_lang(&req_data.lang) {
+    return Err(RespErr::BadRequest { msg: String::from("Language is currently unsupported") });
+  }
+  let pending = ctx.db.cv_dry_runs
+    .find_one(
+      doc! {
+      "requester": &username,
+      "status": { "$in": [CVStatus::Queued.to_string(), CVStatus::InProgress.to_string()] },
+    }
+    ).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  if pending.is_some() {
+    return Err(RespErr::BadRequest { msg: String::from("A dry run is already pending") });
+  }
+  let id = ObjectId::new();
+  let mut build = new_cv_contract(&ctx, &id.to_hex(), &id.to_hex(), &req_data.lang, username.clone(), &req_data.settings).await?;
+  set_git_source(&mut build, &req_data.source)?;
+  let dry_run = CVDryRun {
+    id,
+    requester: username,
+    status: CVStatus::Queued.to_string(),
+    request_ts: build.request_ts,
+    started_ts: None,
+    completed_ts: None,
+    build,
+    git_commit: None,
+    output_cid: None,
+    exports: None,
+    error: None,
+    truncated: false,
+    logs: Vec::new(),
+  };
+  // a concurrent request may have queued a dry run since the check above
+  ctx.db.cv_dry_runs.insert_one(dry_run).await.map_err(|e| {
+    match is_duplicate_key(&e) {
+      true => RespErr::BadRequest { msg: String::from("A dry run is already pending") },
+      false => RespErr::DbErr { msg: e.to_string() },
+    }
+  })?;
+  ctx.compiler.clone().unwrap().notify();
+  Ok(HttpResponse::Ok().json(ResCompile { id: id.to_hex() }))
+}
+
+#[utoipa::path(
+  get,
+  path = "/compile/{id}",
+  context_path = "/cv-api/v1",
+  summary = "Lookup a dry run compilation",
+  responses(
+    (status = 200, description = "Dry run status, compiled output CID and build logs", body = CVDryRunResult),
+    (status = 404, description = "Dry run not found", body = ErrorRes)
+  ),
+  params(("id" = String, Path, description = "Dry run ID"))
+)]
+#[get("/compile/{id}")]
+async fn compile_info(path: web::Path<String>, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let id = ObjectId::parse_str(path.into_inner()).map_err(|_| RespErr::CvDryRunNotFound)?;
+  let d = ctx.db.cv_dry_runs
+    .find_one(doc! { "_id": id }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvDryRunNotFound)?;
+  let fmt_ts = |ts: DateTime| ts.to_chrono().format(TIMESTAMP_FORMAT).to_string();
+  Ok(
+    HttpResponse::Ok().json(CVDryRunResult {
+      id: d.id.to_hex(),
+      status: d.status,
+      lang: d.build.lang,
+      repo_url: d.build.repo_url.unwrap_or_default(),
+      git_commit: d.git_commit,
+      request_ts: fmt_ts(d.request_ts),
+      started_ts: d.started_ts.map(fmt_ts),
+      completed_ts: d.completed_ts.map(fmt_ts),
+      output_cid: d.output_cid,
+      exports: d.exports,
+      error: d.error,
+      truncated: d.truncated,
+      logs: d.logs,
+    })
+  )
+}
+
+/// Checks that the requester is a configured admin
+fn verify_admin(req: &HttpRequest) -> Result<String, RespErr> {
+  let username = verify_auth_token(req)?;
+  let admins = config.compiler.as_ref().and_then(|c| c.admins.clone()).unwrap_or_default();
+  if !config.auth.enabled || !admins.contains(&username) {
+    return Err(RespErr::CvAdminOnly);
+  }
+  Ok(username)
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ReqMirrorBackfill {
+  /// Contract address to mirror again even if it has used up its mirror attempts. All pending contracts are mirrored if not specified.
+  address: Option<String>,
+}
+
+#[utoipa::path(
+  post,
+  path = "/admin/gitea/backfill",
+  context_path = "/cv-api/v1",
+  summary = "Run the Gitea mirror backfill job",
+  description = "Mirror verified contracts without a Gitea mirror now instead of waiting for the next scheduled run. Admin only.",
+  responses(
+    (status = 200, description = "Backfill job triggered", body = SuccessRes),
+    (status = 400, description = "Gitea mirroring is not configured", body = ErrorRes),
+    (status = 403, description = "Requester is not an admin", body = ErrorRes),
+    (status = 404, description = "Verified contract not found", body = ErrorRes)
+  ),
+  request_body = ReqMirrorBackfill
+)]
+#[post("/admin/gitea/backfill")]
+async fn mirror_backfill(
+  req: HttpRequest,
+  req_data: web::Json<ReqMirrorBackfill>,
+  ctx: web::Data<Context>
+) -> Result<HttpResponse, RespErr> {
+  let compiler = ctx.compiler.clone().ok_or(RespErr::CvDisabled)?;
+  verify_admin(&req)?;
+  if let Some(addr) = &req_data.address {
+    let deployed_contract = ctx.db.contracts
+      .find_one(doc! { "id": addr }).await
+      .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+      .ok_or(RespErr::ContractNotFound)?;
+    let result = ctx.db.cv_contracts
+      .update_one(
+        doc! { "_id": &deployed_contract.code, "status": CVStatus::Success.to_string() },
+        doc! { "$set": { "gitea_attempts": 0 } }
+      ).await
+      .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    if result.matched_count == 0 {
+      return Err(RespErr::ContractNotFound);
+    }
+  }
+  if !compiler.trigger_mirror_backfill() {
+    return Err(RespErr::BadRequest { msg: String::from("Gitea mirroring is not configured") });
+  }
+  Ok(HttpResponse::Ok().json(SuccessRes { success: true }))
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+struct ReqWebhook {
+  /// HTTPS URL notified whenever a verification requested by the user finishes. Leave empty to only receive callbacks
+  /// of requests specifying a `callback_url`.
+  url: Option<String>,
+}
+
+/// Username of the requester for webhook management, which requires authentication to be enabled
+fn webhook_user(req: &HttpRequest, ctx: &Context) -> Result<String, RespErr> {
+  if ctx.compiler.is_none() {
+    return Err(RespErr::CvDisabled);
+  }
+  let username = verify_auth_token(req)?;
+  if username.is_empty() {
+    return Err(RespErr::BadRequest { msg: String::from("Webhooks require authentication to be enabled") });
+  }
+  Ok(username)
+}
+
+#[utoipa::path(
+  post,
+  path = "/webhook",
+  context_path = "/cv-api/v1",
+  summary = "Register a webhook",
+  description = "Register or replace the webhook of the authenticated user. A new signing secret is generated on every registration and only returned in this response. Deliveries are signed with HMAC-SHA256 over the raw request body in the `X-Magi-Signature` header as `sha256=<hex>`.",
+  responses(
+    (status = 200, description = "Webhook registered successfully", body = CVWebhookResult),
+    (status = 400, description = "Failed to register webhook", body = ErrorRes)
+  ),
+  request_body = ReqWebhook
+)]
+#[post("/webhook")]
+async fn webhook_register(
+  req: HttpRequest,
+  req_data: web::Json<ReqWebhook>,
+  ctx: web::Data<Context>
+) -> Result<HttpResponse, RespErr> {
+  let username = webhook_user(&req, &ctx)?;
+  let url = req_data.url.clone().filter(|u| !u.is_empty());
+  if url.as_ref().is_some_and(|u| !is_valid_webhook_url(u)) {
+    return Err(RespErr::CvInvalidCallbackURL);
+  }
+  let webhook = CVWebhook {
+    username: username.clone(),
+    url,
+    secret: hex::encode(rand::rng().random::<[u8; 32]>()),
+    created_ts: DateTime::from_chrono(Utc::now()),
+  };
+  ctx.db.cv_webhooks
+    .replace_one(doc! { "_id": &username }, &webhook)
+    .upsert(true).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  Ok(
+    HttpResponse::Ok().json(CVWebhookResult {
+      url: webhook.url,
+      secret: Some(webhook.secret),
+      created_ts: webhook.created_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+    })
+  )
+}
+
+#[utoipa::path(
+  get,
+  path = "/webhook",
+  context_path = "/cv-api/v1",
+  summary = "Lookup the registered webhook",
+  responses(
+    (status = 200, description = "Webhook of the authenticated user", body = CVWebhookResult),
+    (status = 404, description = "Webhook not found", body = ErrorRes)
+  )
+)]
+#[get("/webhook")]
+async fn webhook_info(req: HttpRequest, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let username = webhook_user(&req, &ctx)?;
+  let webhook = ctx.db.cv_webhooks
+    .find_one(doc! { "_id": &username }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?
+    .ok_or(RespErr::CvWebhookNotFound)?;
+  Ok(
+    HttpResponse::Ok().json(CVWebhookResult {
+      url: webhook.url,
+      secret: None,
+      created_ts: webhook.created_ts.to_chrono().format(TIMESTAMP_FORMAT).to_string(),
+    })
+  )
+}
+
+#[utoipa::path(
+  delete,
+  path = "/webhook",
+  context_path = "/cv-api/v1",
+  summary = "Remove the registered webhook",
+  description = "Remove the webhook of the authenticated user. Pending deliveries are no longer retried.",
+  responses(
+    (status = 200, description = "Webhook removed successfully", body = SuccessRes),
+    (status = 404, description = "Webhook not found", body = ErrorRes)
+  )
+)]
+#[delete("/webhook")]
+async fn webhook_remove(req: HttpRequest, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let username = webhook_user(&req, &ctx)?;
+  let result = ctx.db.cv_webhooks
+    .delete_one(doc! { "_id": &username }).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  if result.deleted_count == 0 {
+    return Err(RespErr::CvWebhookNotFound);
+  }
+  Ok(HttpResponse::Ok().json(SuccessRes { success: true }))
+}
+
+#[utoipa::path(
+  get,
+  path = "/webhook/deliveries",
+  context_path = "/cv-api/v1",
+  summary = "List recent webhook deliveries",
+  responses(
+    (status = 200, description = "Latest 50 webhook deliveries of the authenticated user", body = Vec<CVWebhookDeliveryResult>),
+    (status = 400, description = "Failed to query webhook deliveries", body = ErrorRes)
+  )
+)]
+#[get("/webhook/deliveries")]
+async fn webhook_deliveries(req: HttpRequest, ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let username = webhook_user(&req, &ctx)?;
+  let fmt_ts = |ts: DateTime| ts.to_chrono().format(TIMESTAMP_FORMAT).to_string();
+  let mut cursor = ctx.db.cv_deliveries
+    .find(doc! { "subscriber": &username })
+    .with_options(FindOptions::builder().sort(doc! { "created_ts": -1 }).limit(50).build()).await
+    .map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+  let mut result = Vec::new();
+  while let Some(delivery) = cursor.next().await {
+    let d = delivery.map_err(|e| RespErr::DbErr { msg: e.to_string() })?;
+    result.push(CVWebhookDeliveryResult {
+      id: d.id.to_hex(),
+      url: d.url,
+      address: d.contract_id,
+      status: d.status,
+      delivered: d.delivered,
+      attempts: d.attempts
+        .into_iter()
+        .map(|a| CVDeliveryAttemptResult { ts: fmt_ts(a.ts), status_code: a.status_code, error: a.error })
+        .collect(),
+      next_attempt_ts: d.next_attempt_ts.map(fmt_ts),
+    });
+  }
+  Ok(HttpResponse::Ok().json(result))
+}
+
 #[utoipa::path(
   get,
   path = "/gocompiler/versions",
@@ -381,12 +1697,42 @@ async fn contract_info(path: web::Path<String>, ctx: web::Data<Context>) -> Resu
   responses((status = 200, description = "List of TinyGo compiler versions with versions of its main dependencies", body = HashMap<String, CVTinyGoLibVersions>))
 )]
 #[get("/gocompiler/versions")]
-async fn gocompiler_versions() -> Result<HttpResponse, RespErr> {
-  let versions = tinygo_versions.clone();
+async fn gocompiler_versions(ctx: web::Data<Context>) -> Result<HttpResponse, RespErr> {
+  let versions = tinygo_version_table(&ctx.db, config.compiler.as_ref()).await.map_err(|e| RespErr::DbErr {
+    msg: e.to_string(),
+  })?;
   let result = serde_json::to_value(&versions).expect("Should serialize to json correctly");
   return Ok(HttpResponse::Ok().json(result));
 }
 
+#[utoipa::path(
+  get,
+  path = "/rustc/versions",
+  context_path = "/cv-api/v1",
+  summary = "List supported Rust compiler versions",
+  responses((status = 200, description = "List of Rust compiler versions with its LLVM version and toolchain image", body = HashMap<String, CVRustLibVersions>))
+)]
+#[get("/rustc/versions")]
+async fn rustc_versions() -> Result<HttpResponse, RespErr> {
+  let versions = rust_versions.clone();
+  let result = serde_json::to_value(&versions).expect("Should serialize to json correctly");
+  Ok(HttpResponse::Ok().json(result))
+}
+
+#[utoipa::path(
+  get,
+  path = "/asc/versions",
+  context_path = "/cv-api/v1",
+  summary = "List supported AssemblyScript compiler versions",
+  responses((status = 200, description = "List of AssemblyScript compiler versions with its Node.js version and toolchain image", body = HashMap<String, CVAscLibVersions>))
+)]
+#[get("/asc/versions")]
+async fn asc_compiler_versions() -> Result<HttpResponse, RespErr> {
+  let versions = asc_versions.clone();
+  let result = serde_json::to_value(&versions).expect("Should serialize to json correctly");
+  Ok(HttpResponse::Ok().json(result))
+}
+
 #[derive(OpenApi)]
 #[openapi(
   info(
@@ -394,7 +1740,34 @@ async fn gocompiler_versions() -> Result<HttpResponse, RespErr> {
     description = "Verifies Magi contracts by compiling the uploaded contract source code and comparing the resulting output bytecode against the deployed contract bytecode.",
     license(name = "MIT")
   ),
-  paths(verify_new, contract_info, gocompiler_versions),
+  paths(
+    verify_new,
+    verify_upload,
+    contract_info,
+    contract_logs,
+    contract_events,
+    contract_attempts,
+    attempt_logs,
+    contract_attestation,
+    attestation_key,
+    contract_files,
+    contract_file,
+    contract_bundle,
+    contract_sbom,
+    sbom_dependents,
+    search_code,
+    verification_queue,
+    compile_new,
+    compile_info,
+    mirror_backfill,
+    webhook_register,
+    webhook_info,
+    webhook_remove,
+    webhook_deliveries,
+    gocompiler_versions,
+    rustc_versions,
+    asc_compiler_versions
+  ),
   components(responses(ErrorRes, SuccessRes))
 )]
 struct OpenApiDoc;
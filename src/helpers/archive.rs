@@ -0,0 +1,267 @@
+use flate2::{ read::GzDecoder, write::GzEncoder, Compression };
+use std::{ fs, io::{ self, Cursor, Read }, path::{ Component, Path, PathBuf } };
+use tar::EntryType;
+use zip::ZipArchive;
+use crate::config::CompilerConf;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArchiveKind {
+  TarGz,
+  Zip,
+}
+
+impl ArchiveKind {
+  /// Detect the archive format from its magic bytes
+  pub fn detect(data: &[u8]) -> Option<Self> {
+    if data.starts_with(&[0x1f, 0x8b]) {
+      Some(ArchiveKind::TarGz)
+    } else if data.starts_with(b"PK\x03\x04") {
+      Some(ArchiveKind::Zip)
+    } else {
+      None
+    }
+  }
+
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ArchiveKind::TarGz => "tar.gz",
+      ArchiveKind::Zip => "zip",
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+pub struct ArchiveLimits {
+  /// Maximum total uncompressed size of all files
+  pub max_size: u64,
+  /// Maximum number of entries
+  pub max_entries: usize,
+}
+
+impl ArchiveLimits {
+  /// Limits derived from the compiler config, extracted size is capped at the maximum repository size
+  pub fn from_conf(conf: &CompilerConf) -> Self {
+    ArchiveLimits {
+      max_size: (conf.max_repo_size.unwrap_or(102400) as u64) * 1024,
+      max_entries: 10000,
+    }
+  }
+}
+
+/// Relative path of an archive entry, rejecting absolute paths and parent directory traversal
+fn sanitize_path(path: &Path) -> Result<Option<PathBuf>, String> {
+  let mut result = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::Normal(c) => result.push(c),
+      Component::CurDir => (),
+      _ => {
+        return Err(format!("Invalid path in archive: {}", path.display()));
+      }
+    }
+  }
+  Ok(if result.as_os_str().is_empty() { None } else { Some(result) })
+}
+
+/// Walks through an archive with limits enforced, writing its contents into `dest` if specified
+fn walk_archive(data: &[u8], kind: ArchiveKind, limits: ArchiveLimits, dest: Option<&Path>) -> Result<(), String> {
+  let mut entries = 0;
+  let mut total_size: u64 = 0;
+  let mut handle_entry = |path: Option<PathBuf>, is_dir: bool, reader: &mut dyn Read| -> Result<(), String> {
+    entries += 1;
+    if entries > limits.max_entries {
+      return Err(format!("Archive contains more than {} entries", limits.max_entries));
+    }
+    let path = match path {
+      Some(p) => p,
+      None => {
+        return Ok(());
+      }
+    };
+    let target = dest.map(|d| d.join(&path));
+    if is_dir {
+      if let Some(t) = target {
+        fs::create_dir_all(t).map_err(|e| e.to_string())?;
+      }
+      return Ok(());
+    }
+    // enforce the size limit on the actual decompressed stream rather than the declared entry size
+    let remaining = limits.max_size - total_size;
+    let mut limited = reader.take(remaining + 1);
+    let written = match target {
+      Some(t) => {
+        if let Some(parent) = t.parent() {
+          fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let mut file = fs::File::create(&t).map_err(|e| e.to_string())?;
+        io::copy(&mut limited, &mut file).map_err(|e| e.to_string())?
+      }
+      None => io::copy(&mut limited, &mut io::sink()).map_err(|e| e.to_string())?,
+    };
+    if written > remaining {
+      return Err(format!("Archive exceeds the uncompressed size limit of {} bytes", limits.max_size));
+    }
+    total_size += written;
+    Ok(())
+  };
+  match kind {
+    ArchiveKind::TarGz => {
+      let mut archive = tar::Archive::new(GzDecoder::new(data));
+      for entry in archive.entries().map_err(|e| e.to_string())? {
+        let mut entry = entry.map_err(|e| e.to_string())?;
+        let is_dir = match entry.header().entry_type() {
+          EntryType::Regular | EntryType::Continuous => false,
+          EntryType::Directory => true,
+          EntryType::XGlobalHeader | EntryType::XHeader => {
+            continue;
+          }
+          t => {
+            return Err(format!("Unsupported archive entry type {:?}", t));
+          }
+        };
+        let path = sanitize_path(&entry.path().map_err(|e| e.to_string())?)?;
+        handle_entry(path, is_dir, &mut entry)?;
+      }
+    }
+    ArchiveKind::Zip => {
+      let mut archive = ZipArchive::new(Cursor::new(data)).map_err(|e| e.to_string())?;
+      if archive.len() > limits.max_entries {
+        return Err(format!("Archive contains more than {} entries", limits.max_entries));
+      }
+      for i in 0..archive.len() {
+        let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
+        if file.is_symlink() {
+          return Err(format!("Symbolic links are not allowed in archive: {}", file.name()));
+        }
+        let path = sanitize_path(Path::new(file.name()))?;
+        let is_dir = file.is_dir();
+        handle_entry(path, is_dir, &mut file)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Validate an archive against the limits without extracting it
+pub fn validate_archive(data: &[u8], kind: ArchiveKind, limits: ArchiveLimits) -> Result<(), String> {
+  walk_archive(data, kind, limits, None)
+}
+
+/// Extract an archive into `dest`. If the archive contains a single top-level directory, its contents are moved up into `dest`.
+pub fn extract_archive(data: &[u8], kind: ArchiveKind, limits: ArchiveLimits, dest: &Path) -> Result<(), String> {
+  walk_archive(data, kind, limits, Some(dest))?;
+  let top_level = fs
+    ::read_dir(dest)
+    .map_err(|e| e.to_string())?
+    .filter_map(|e| e.ok())
+    .collect::<Vec<_>>();
+  if top_level.len() == 1 && top_level[0].path().is_dir() {
+    let root = dest.join(".archive-root");
+    fs::rename(top_level[0].path(), &root).map_err(|e| e.to_string())?;
+    for entry in fs::read_dir(&root).map_err(|e| e.to_string())? {
+      let entry = entry.map_err(|e| e.to_string())?;
+      fs::rename(entry.path(), dest.join(entry.file_name())).map_err(|e| e.to_string())?;
+    }
+    fs::remove_dir(&root).map_err(|e| e.to_string())?;
+  }
+  Ok(())
+}
+
+/// Build a `.tar.gz` archive of regular files with the given modification time, entries are written in order
+pub fn build_tar_gz(files: &[(String, Vec<u8>)], mtime: u64) -> Result<Vec<u8>, String> {
+  let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+  for (path, data) in files {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_entry_type(EntryType::Regular);
+    header.set_cksum();
+    builder.append_data(&mut header, path, data.as_slice()).map_err(|e| e.to_string())?;
+  }
+  builder
+    .into_inner()
+    .map_err(|e| e.to_string())?
+    .finish()
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Write;
+
+  const LIMITS: ArchiveLimits = ArchiveLimits { max_size: 1024, max_entries: 10 };
+
+  fn tar_gz(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default()));
+    for (name, content) in files {
+      let mut header = tar::Header::new_gnu();
+      header.set_size(content.len() as u64);
+      header.set_mode(0o644);
+      header.set_cksum();
+      builder.append_data(&mut header, name, *content).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap()
+  }
+
+  fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, content) in files {
+      writer.start_file(*name, zip::write::SimpleFileOptions::default()).unwrap();
+      writer.write_all(content).unwrap();
+    }
+    writer.finish().unwrap().into_inner()
+  }
+
+  #[test]
+  fn detect_kind() {
+    assert_eq!(ArchiveKind::detect(&tar_gz(&[("a", b"a")])), Some(ArchiveKind::TarGz));
+    assert_eq!(ArchiveKind::detect(&zip(&[("a", b"a")])), Some(ArchiveKind::Zip));
+    assert_eq!(ArchiveKind::detect(b"hello"), None);
+  }
+
+  #[test]
+  fn path_traversal() {
+    assert!(sanitize_path(Path::new("../etc/passwd")).is_err());
+    assert!(sanitize_path(Path::new("/etc/passwd")).is_err());
+    assert!(sanitize_path(Path::new("a/../../b")).is_err());
+    assert_eq!(sanitize_path(Path::new("./a/b.go")).unwrap(), Some(PathBuf::from("a/b.go")));
+    assert!(validate_archive(&zip(&[("../evil", b"x")]), ArchiveKind::Zip, LIMITS).is_err());
+  }
+
+  #[test]
+  fn size_limits() {
+    let big = vec![0u8; 2048];
+    assert!(validate_archive(&tar_gz(&[("big", &big)]), ArchiveKind::TarGz, LIMITS).is_err());
+    assert!(validate_archive(&zip(&[("big", &big)]), ArchiveKind::Zip, LIMITS).is_err());
+    let many: Vec<(String, &[u8])> = (0..11).map(|i| (format!("f{}", i), b"x" as &[u8])).collect();
+    let many: Vec<(&str, &[u8])> = many
+      .iter()
+      .map(|(n, c)| (n.as_str(), *c))
+      .collect();
+    assert!(validate_archive(&zip(&many), ArchiveKind::Zip, LIMITS).is_err());
+    assert!(validate_archive(&tar_gz(&[("ok", b"ok")]), ArchiveKind::TarGz, LIMITS).is_ok());
+  }
+
+  #[test]
+  fn extract_strips_single_root() {
+    let dest = std::env::temp_dir().join(format!("magi-bb-archive-test-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dest);
+    fs::create_dir_all(&dest).unwrap();
+    let data = tar_gz(&[("repo-main/go.mod", b"module x"), ("repo-main/contract/main.go", b"package main")]);
+    extract_archive(&data, ArchiveKind::TarGz, LIMITS, &dest).unwrap();
+    assert!(dest.join("go.mod").is_file());
+    assert!(dest.join("contract/main.go").is_file());
+    fs::remove_dir_all(&dest).unwrap();
+  }
+
+  #[test]
+  fn build_tar_gz_roundtrip() {
+    let files = vec![(String::from("bundle/metadata.json"), b"{}".to_vec()), (String::from("bundle/build.wasm"), vec![0, 97, 115, 109])];
+    let data = build_tar_gz(&files, 1700000000).unwrap();
+    assert_eq!(ArchiveKind::detect(&data), Some(ArchiveKind::TarGz));
+    assert!(validate_archive(&data, ArchiveKind::TarGz, LIMITS).is_ok());
+    assert_eq!(build_tar_gz(&files, 1700000000).unwrap(), data);
+  }
+}
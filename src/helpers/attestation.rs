@@ -0,0 +1,95 @@
+use ed25519_dalek::{ Signature, Signer, SigningKey, Verifier, VerifyingKey };
+use crate::types::cv::CVAttestationPayload;
+
+/// Signature algorithm of verification attestations
+pub const ALGORITHM: &str = "ed25519";
+
+/// Signing key from its hex encoded 32 byte secret key
+pub fn signing_key(secret_hex: &str) -> Result<SigningKey, String> {
+  let bytes: [u8; 32] = hex
+    ::decode(secret_hex)
+    .map_err(|e| e.to_string())?
+    .try_into()
+    .map_err(|_| String::from("Attestation key must be 32 bytes"))?;
+  Ok(SigningKey::from_bytes(&bytes))
+}
+
+/// Hex encoded public key of a signing key
+pub fn public_key(key: &SigningKey) -> String {
+  hex::encode(key.verifying_key().to_bytes())
+}
+
+/// Canonical form of an attestation, which is its compact JSON serialization with fields in declaration order
+pub fn canonical_payload(payload: &CVAttestationPayload) -> String {
+  serde_json::to_string(payload).expect("Should serialize to json correctly")
+}
+
+/// Sign an attestation, returning its canonical form and the hex encoded signature over it
+pub fn sign(key: &SigningKey, payload: &CVAttestationPayload) -> (String, String) {
+  let canonical = canonical_payload(payload);
+  let signature = key.sign(canonical.as_bytes());
+  (canonical, hex::encode(signature.to_bytes()))
+}
+
+/// Verify the signature of an attestation against a hex encoded public key, returning the attested statement.
+/// Payloads that are not in canonical form are rejected. Provided for consumers of the library crate.
+#[allow(dead_code)]
+pub fn verify(payload: &str, signature: &str, public_key: &str) -> Result<CVAttestationPayload, String> {
+  let key_bytes: [u8; 32] = hex
+    ::decode(public_key)
+    .map_err(|e| e.to_string())?
+    .try_into()
+    .map_err(|_| String::from("Public key must be 32 bytes"))?;
+  let key = VerifyingKey::from_bytes(&key_bytes).map_err(|e| e.to_string())?;
+  let sig_bytes: [u8; 64] = hex
+    ::decode(signature)
+    .map_err(|e| e.to_string())?
+    .try_into()
+    .map_err(|_| String::from("Signature must be 64 bytes"))?;
+  key.verify(payload.as_bytes(), &Signature::from_bytes(&sig_bytes)).map_err(|e| e.to_string())?;
+  let statement: CVAttestationPayload = serde_json::from_str(payload).map_err(|e| e.to_string())?;
+  if canonical_payload(&statement) != payload {
+    return Err(String::from("Attestation payload is not in canonical form"));
+  }
+  Ok(statement)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn statement() -> CVAttestationPayload {
+    CVAttestationPayload {
+      version: 1,
+      code: String::from("bafkreiexample"),
+      contract_id: String::from("vsc1example"),
+      lang: String::from("go"),
+      repo_url: Some(String::from("https://github.com/techcoderx/go-contract-template")),
+      git_commit: Some(String::from("0123456789abcdef0123456789abcdef01234567")),
+      source_archive: None,
+      tinygo_version: Some(String::from("0.39.0")),
+      go_version: Some(String::from("1.25.0")),
+      llvm_version: Some(String::from("19.1.2")),
+      rust_version: None,
+      cargo_profile: None,
+      asc_version: None,
+      node_version: None,
+      toolchain_image: Some(String::from("tinygo/tinygo@sha256:00")),
+      post_process: None,
+      strip_tool: Some(String::from("wabt")),
+      verified_ts: String::from("2026-01-01T00:00:00+00:00"),
+    }
+  }
+
+  #[test]
+  fn sign_and_verify() {
+    let key = signing_key(&hex::encode([7u8; 32])).unwrap();
+    let (payload, signature) = sign(&key, &statement());
+    let verified = verify(&payload, &signature, &public_key(&key)).unwrap();
+    assert_eq!(verified.code, "bafkreiexample");
+    let tampered = payload.replace("vsc1example", "vsc1other");
+    assert!(verify(&tampered, &signature, &public_key(&key)).is_err());
+    let other = signing_key(&hex::encode([8u8; 32])).unwrap();
+    assert!(verify(&payload, &signature, &public_key(&other)).is_err());
+  }
+}
@@ -1,2 +1,4 @@
 pub mod db;
 pub mod datetime;
+pub mod archive;
+pub mod attestation;
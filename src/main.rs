@@ -60,15 +60,13 @@ async fn main() -> std::io::Result<()> {
       .unwrap_or(false)
   {
     true => {
-      let ccf = config.compiler.clone().unwrap();
-      if ccf.github_api_key.is_none() {
-        error!("Missing Github API key for compiler");
-        process::exit(1);
-      }
       Some(compiler::Compiler::init(&db, &http_client, &config.gocompiler, &config.compiler.clone().unwrap(), config.gitea.clone()))
     }
     false => None,
   };
+  if let Some(c) = &compiler {
+    c.start();
+  }
   if config.be_indexer.unwrap_or(false) {
     let idxer = indexer::indexer::Indexer::init(&http_client, &db);
     idxer.start();
@@ -86,14 +84,38 @@ async fn main() -> std::io::Result<()> {
       .wrap(cors)
       .wrap(NormalizePath::trim())
       .app_data(web::Data::new(server_ctx.clone()))
+      .app_data(cv_api::upload_form_config())
       .service(
         web
           ::scope("/cv-api/v1")
           .service(cv_api::hello)
           .service(cv_api::login)
           .service(cv_api::verify_new)
+          .service(cv_api::verify_upload)
           .service(cv_api::contract_info)
+          .service(cv_api::contract_logs)
+          .service(cv_api::contract_events)
+          .service(cv_api::contract_attempts)
+          .service(cv_api::attempt_logs)
+          .service(cv_api::contract_attestation)
+          .service(cv_api::attestation_key)
+          .service(cv_api::contract_bundle)
+          .service(cv_api::contract_sbom)
+          .service(cv_api::sbom_dependents)
+          .service(cv_api::search_code)
+          .service(cv_api::contract_files)
+          .service(cv_api::contract_file)
+          .service(cv_api::verification_queue)
+          .service(cv_api::compile_new)
+          .service(cv_api::compile_info)
+          .service(cv_api::mirror_backfill)
+          .service(cv_api::webhook_register)
+          .service(cv_api::webhook_info)
+          .service(cv_api::webhook_remove)
+          .service(cv_api::webhook_deliveries)
           .service(cv_api::gocompiler_versions)
+          .service(cv_api::rustc_versions)
+          .service(cv_api::asc_compiler_versions)
       )
       .service(
         web
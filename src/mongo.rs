@@ -1,11 +1,32 @@
 use clap::Parser;
-use mongodb::{ Client, Collection, Database, IndexModel, options::ClientOptions };
+use mongodb::{
+  error::{ ErrorKind, WriteFailure },
+  options::{ ClientOptions, IndexOptions },
+  Client,
+  Collection,
+  Database,
+  IndexModel,
+};
 use std::error::Error;
 use log::info;
 use crate::{
   config::{ self, DbConf },
   types::{
-    cv::CVContract,
+    cv::{
+      CVAttempt,
+      CVAttemptLog,
+      CVAttestation,
+      CVBuildLog,
+      CVContract,
+      CVDryRun,
+      CVSbom,
+      CVSearchPosting,
+      CVSourceTree,
+      CVStatus,
+      CVTinyGoVersionRecord,
+      CVWebhook,
+      CVWebhookDelivery,
+    },
     vsc::{
       BlockHeaderRecord,
       BridgeStats,
@@ -49,6 +70,17 @@ pub struct MongoDB {
 
   // contract verifier
   pub cv_contracts: Collection<CVContract>,
+  pub cv_logs: Collection<CVBuildLog>,
+  pub cv_sources: Collection<CVSourceTree>,
+  pub cv_tinygo_versions: Collection<CVTinyGoVersionRecord>,
+  pub cv_webhooks: Collection<CVWebhook>,
+  pub cv_deliveries: Collection<CVWebhookDelivery>,
+  pub cv_dry_runs: Collection<CVDryRun>,
+  pub cv_attestations: Collection<CVAttestation>,
+  pub cv_attempts: Collection<CVAttempt>,
+  pub cv_attempt_logs: Collection<CVAttemptLog>,
+  pub cv_search: Collection<CVSearchPosting>,
+  pub cv_sboms: Collection<CVSbom>,
 }
 
 impl MongoDB {
@@ -62,10 +94,26 @@ impl MongoDB {
       drop_db(&db2).await;
     }
     let cv_contracts: Collection<CVContract> = db3.collection("contracts");
-    let is_setup = db3.list_collection_names().await?.contains(&String::from("contracts"));
-    if !is_setup {
+    let cv_attempts: Collection<CVAttempt> = db3.collection("attempts");
+    let cv_search: Collection<CVSearchPosting> = db3.collection("source_index");
+    let cv_sboms: Collection<CVSbom> = db3.collection("sboms");
+    let cv_dry_runs: Collection<CVDryRun> = db3.collection("dry_runs");
+    let collections = db3.list_collection_names().await?;
+    if !collections.contains(&String::from("contracts")) {
       MongoDB::setup_cv_db(&cv_contracts).await?;
     }
+    if !collections.contains(&String::from("attempts")) {
+      MongoDB::setup_cv_attempts(&cv_attempts).await?;
+    }
+    if !collections.contains(&String::from("source_index")) {
+      MongoDB::setup_cv_search(&cv_search).await?;
+    }
+    if !collections.contains(&String::from("sboms")) {
+      MongoDB::setup_cv_sboms(&cv_sboms).await?;
+    }
+    if !collections.contains(&String::from("dry_runs")) {
+      MongoDB::setup_cv_dry_runs(&cv_dry_runs).await?;
+    }
     info!("Connected to Magi MongoDB database successfully");
     Ok(MongoDB {
       contracts: db.collection("contracts"),
@@ -82,6 +130,17 @@ impl MongoDB {
       bridge_stats: db2.collection("bridge_stats"),
       network_stats: db2.collection("network_stats"),
       cv_contracts: cv_contracts,
+      cv_logs: db3.collection("build_logs"),
+      cv_sources: db3.collection("source_trees"),
+      cv_tinygo_versions: db3.collection("tinygo_versions"),
+      cv_webhooks: db3.collection("webhooks"),
+      cv_deliveries: db3.collection("webhook_deliveries"),
+      cv_dry_runs,
+      cv_attestations: db3.collection("attestations"),
+      cv_attempts,
+      cv_attempt_logs: db3.collection("attempt_logs"),
+      cv_search,
+      cv_sboms,
     })
   }
 
@@ -97,6 +156,11 @@ impl MongoDB {
       .build();
     contracts_db.create_index(status_ts_index).await?;
 
+    let status_verified_index = IndexModel::builder()
+      .keys(bson::doc! { "status": 1, "verified_ts": -1 })
+      .build();
+    contracts_db.create_index(status_verified_index).await?;
+
     let contract_id_idx = IndexModel::builder()
       .keys(bson::doc! { "contract_id": 1 })
       .build();
@@ -104,4 +168,62 @@ impl MongoDB {
 
     Ok(())
   }
+
+  pub async fn setup_cv_dry_runs(dry_runs_db: &Collection<CVDryRun>) -> Result<(), Box<dyn Error>> {
+    // at most one pending dry run per requester
+    let pending_idx = IndexModel::builder()
+      .keys(bson::doc! { "requester": 1 })
+      .options(
+        IndexOptions::builder()
+          .unique(true)
+          .partial_filter_expression(bson::doc! { "status": { "$in": [CVStatus::Queued.to_string(), CVStatus::InProgress.to_string()] } })
+          .build()
+      )
+      .build();
+    dry_runs_db.create_index(pending_idx).await?;
+
+    Ok(())
+  }
+
+  pub async fn setup_cv_attempts(attempts_db: &Collection<CVAttempt>) -> Result<(), Box<dyn Error>> {
+    let code_ts_idx = IndexModel::builder()
+      .keys(bson::doc! { "code": 1, "request_ts": -1 })
+      .build();
+    attempts_db.create_index(code_ts_idx).await?;
+
+    Ok(())
+  }
+
+  pub async fn setup_cv_search(search_db: &Collection<CVSearchPosting>) -> Result<(), Box<dyn Error>> {
+    let token_idx = IndexModel::builder()
+      .keys(bson::doc! { "token": 1, "code": 1 })
+      .build();
+    search_db.create_index(token_idx).await?;
+
+    let code_idx = IndexModel::builder()
+      .keys(bson::doc! { "code": 1 })
+      .build();
+    search_db.create_index(code_idx).await?;
+
+    Ok(())
+  }
+
+  pub async fn setup_cv_sboms(sboms_db: &Collection<CVSbom>) -> Result<(), Box<dyn Error>> {
+    let path_idx = IndexModel::builder()
+      .keys(bson::doc! { "components.path": 1, "components.version": 1 })
+      .build();
+    sboms_db.create_index(path_idx).await?;
+
+    let replace_idx = IndexModel::builder()
+      .keys(bson::doc! { "components.replace_path": 1 })
+      .build();
+    sboms_db.create_index(replace_idx).await?;
+
+    Ok(())
+  }
+}
+
+/// Whether a write failed on a unique index
+pub fn is_duplicate_key(e: &mongodb::error::Error) -> bool {
+  matches!(*e.kind, ErrorKind::Write(WriteFailure::WriteError(ref w)) if w.code == 11000)
 }
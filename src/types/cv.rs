@@ -1,13 +1,15 @@
 use serde::{ Deserialize, Serialize };
 use utoipa::{ ToResponse, ToSchema };
-use mongodb::bson::DateTime;
+use mongodb::bson::{ oid::ObjectId, DateTime };
 use lazy_static::lazy_static;
 use std::{ collections::HashMap, fmt };
+use wasm_utils::WasmDiff;
 
 pub enum CVStatus {
   // Pending,
   Queued,
-  // InProgress,
+  InProgress,
+  Retrying,
   Success,
   Failed,
   NotMatch,
@@ -18,7 +20,8 @@ impl fmt::Display for CVStatus {
     match self {
       // CVStatus::Pending => write!(f, "pending"),
       CVStatus::Queued => write!(f, "queued"),
-      // CVStatus::InProgress => write!(f, "in progress"),
+      CVStatus::InProgress => write!(f, "in progress"),
+      CVStatus::Retrying => write!(f, "retrying"),
       CVStatus::Success => write!(f, "success"),
       CVStatus::Failed => write!(f, "failed"),
       CVStatus::NotMatch => write!(f, "not match"),
@@ -26,13 +29,50 @@ impl fmt::Display for CVStatus {
   }
 }
 
-#[derive(Debug, Clone, Serialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CVTinyGoLibVersions {
   pub go: String,
   pub llvm: String,
   pub img_digest: String,
 }
 
+/// TinyGo version entry managed by admins in the `tinygo_versions` collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CVTinyGoVersionRecord {
+  #[serde(rename = "_id")]
+  pub version: String,
+  pub go: String,
+  pub llvm: String,
+  pub img_digest: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CVRustLibVersions {
+  pub llvm: String,
+  pub image: String,
+  /// Digest that pins the image, builds pull the image by digest only
+  pub img_digest: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CVAscLibVersions {
+  pub node: String,
+  pub image: String,
+  /// Digest that pins the image, builds pull the image by digest only
+  pub img_digest: String,
+}
+
+/// Post-processing step applied to the compiled output, pinned to the tool version at request time
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVPostProcessStep {
+  /// Tool name
+  pub tool: String,
+  /// Allowlisted flags passed to the tool
+  pub flags: Vec<String>,
+  /// Pinned tool version
+  pub version: String,
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct CVContract {
   #[serde(rename = "_id")]
@@ -40,12 +80,44 @@ pub struct CVContract {
   pub contract_id: String,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub verifier: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_url: Option<String>,
   pub repo_name: String,
   pub repo_branch: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_tag: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_commit: Option<String>,
   pub git_commit: Option<String>,
-  pub tinygo_version: String,
-  pub go_version: String,
-  pub llvm_version: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub go_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub llvm_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_img_digest: Option<String>,
+  /// Digest of the Rust or AssemblyScript toolchain image the request is pinned to
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub toolchain_img_digest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rust_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_profile: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_features: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub node_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_entry: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_options: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source_archive: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub post_process: Option<Vec<CVPostProcessStep>>,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub strip_tool: Option<String>,
   #[serde(skip_serializing_if = "Option::is_none")]
@@ -55,11 +127,212 @@ pub struct CVContract {
   pub request_ts: DateTime,
   pub verified_ts: Option<DateTime>,
   pub status: String,
+  /// Number of build attempts so far
+  #[serde(default)]
+  pub attempts: i32,
+  /// Timestamp at which a worker claimed the latest attempt
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub started_ts: Option<DateTime>,
+  /// Earliest timestamp of the next attempt while retrying
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub next_attempt_ts: Option<DateTime>,
+  /// Timestamp at which the latest attempt finished with a final outcome
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub completed_ts: Option<DateTime>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub last_error: Option<String>,
   pub exports: Option<Vec<String>>,
   pub license: Option<String>,
   pub lang: String,
   #[serde(skip_serializing_if = "Option::is_none")]
   pub gitea_url: Option<String>,
+  /// Error of the latest failed mirror attempt
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub gitea_error: Option<String>,
+  #[serde(default)]
+  pub gitea_attempts: i32,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub bytecode_diff: Option<WasmDiff>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub callback_url: Option<String>,
+  /// Attempt record of the current verification request
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub attempt_id: Option<ObjectId>,
+  /// Whether build settings are read from the manifest in the source tree
+  #[serde(default)]
+  pub build_manifest: bool,
+  /// Bytecode CID of the previously verified code of the contract if queued automatically after an upgrade
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub upgraded_from: Option<String>,
+}
+
+/// Build settings of a verification request as recorded in its attempt
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVAttemptSettings {
+  /// Language of contract source code
+  pub lang: String,
+  /// HTTPS URL of the git repository (archive verifications only have `source_archive`)
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_branch: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_tag: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_commit: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source_archive: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub go_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub llvm_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_img_digest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub toolchain_img_digest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rust_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_profile: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_features: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub node_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_entry: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_options: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub post_process: Option<Vec<CVPostProcessStep>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub strip_tool: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub contract_dir: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub go_mod_dir: Option<String>,
+}
+
+impl CVAttemptSettings {
+  pub fn from_contract(contract: &CVContract) -> Self {
+    CVAttemptSettings {
+      lang: contract.lang.clone(),
+      repo_url: contract.repo_url.clone(),
+      repo_branch: Some(contract.repo_branch.clone()).filter(|b| !b.is_empty()),
+      repo_tag: contract.repo_tag.clone(),
+      repo_commit: contract.repo_commit.clone(),
+      source_archive: contract.source_archive.clone(),
+      tinygo_version: contract.tinygo_version.clone(),
+      go_version: contract.go_version.clone(),
+      llvm_version: contract.llvm_version.clone(),
+      tinygo_img_digest: contract.tinygo_img_digest.clone(),
+      toolchain_img_digest: contract.toolchain_img_digest.clone(),
+      rust_version: contract.rust_version.clone(),
+      cargo_profile: contract.cargo_profile.clone(),
+      cargo_features: contract.cargo_features.clone(),
+      asc_version: contract.asc_version.clone(),
+      node_version: contract.node_version.clone(),
+      asc_entry: contract.asc_entry.clone(),
+      asc_options: contract.asc_options.clone(),
+      post_process: contract.post_process.clone(),
+      strip_tool: contract.strip_tool.clone(),
+      contract_dir: contract.contract_dir.clone(),
+      go_mod_dir: contract.go_mod_dir.clone(),
+    }
+  }
+}
+
+/// Append-only record of a verification request and its outcome, kept after the contract is requested again
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVAttempt {
+  #[serde(rename = "_id")]
+  pub id: ObjectId,
+  pub code: String,
+  pub contract_id: String,
+  pub requester: Option<String>,
+  pub request_ts: DateTime,
+  pub settings: CVAttemptSettings,
+  pub status: String,
+  /// Number of build attempts of the request
+  #[serde(default)]
+  pub attempts: i32,
+  /// Resolved git commit hash that was built
+  pub git_commit: Option<String>,
+  /// Raw CID of the compiled output
+  pub output_cid: Option<String>,
+  pub last_error: Option<String>,
+  pub completed_ts: Option<DateTime>,
+  /// Whether the build log of the latest build attempt is stored in the attempt logs collection under the attempt ID
+  #[serde(default)]
+  pub has_logs: bool,
+  /// Bytecode CID of the previously verified code of the contract if queued automatically after an upgrade
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub upgraded_from: Option<String>,
+}
+
+impl CVAttempt {
+  /// New attempt record of a queued verification request
+  pub fn queued(contract: &CVContract) -> Self {
+    CVAttempt {
+      id: ObjectId::new(),
+      code: contract.code.clone(),
+      contract_id: contract.contract_id.clone(),
+      requester: contract.verifier.clone(),
+      request_ts: contract.request_ts,
+      settings: CVAttemptSettings::from_contract(contract),
+      status: contract.status.clone(),
+      attempts: 0,
+      git_commit: None,
+      output_cid: None,
+      last_error: None,
+      completed_ts: None,
+      has_logs: false,
+      upgraded_from: contract.upgraded_from.clone(),
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVAttemptLog {
+  #[serde(rename = "_id")]
+  pub id: ObjectId,
+  pub truncated: bool,
+  pub logs: Vec<CVLogEntry>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVAttemptResult {
+  /// Attempt ID
+  pub id: String,
+  /// Contract address that was requested for verification
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Username whom requested the verification
+  pub requester: Option<String>,
+  /// Request timestamp
+  pub request_ts: String,
+  /// Build settings of the request
+  pub settings: CVAttemptSettings,
+  /// Outcome of the request (queued, in progress, retrying, success, failed, not match)
+  pub status: String,
+  /// Number of build attempts of the request
+  pub attempts: i32,
+  /// Resolved git commit hash that was built
+  pub git_commit: Option<String>,
+  /// Raw CID of the compiled output
+  pub output_cid: Option<String>,
+  /// Error of the latest failed build attempt
+  pub last_error: Option<String>,
+  /// Timestamp at which the request finished with a final outcome
+  pub completed_ts: Option<String>,
+  /// Path of the build logs of the attempt
+  pub logs_url: Option<String>,
+  /// Bytecode CID of the previously verified code if the request was queued automatically after a contract upgrade
+  pub upgraded_from: Option<String>,
 }
 
 #[derive(Clone, Serialize, ToResponse, ToSchema)]
@@ -76,23 +349,61 @@ pub struct CVContractResult {
   pub request_ts: String,
   /// Contract verification completion timestamp
   pub verified_ts: Option<String>,
-  /// Contract verification status (pending, queued, in progress, success, failed, not match)
+  /// Contract verification status (queued, in progress, retrying, success, failed, not match)
   pub status: String,
-  /// Repository name
+  /// Number of build attempts so far
+  pub attempts: i32,
+  /// Timestamp at which the latest build attempt started
+  pub started_ts: Option<String>,
+  /// Earliest timestamp of the next build attempt when retrying
+  pub next_attempt_ts: Option<String>,
+  /// Timestamp at which the latest build attempt finished with a final outcome
+  pub completed_ts: Option<String>,
+  /// Error of the latest failed build attempt
+  pub last_error: Option<String>,
+  /// HTTPS URL of the git repository (empty for archive verifications)
+  pub repo_url: String,
+  /// Repository path on the git host (empty for archive verifications)
   pub repo_name: String,
   /// Git branch
   pub repo_branch: String,
+  /// Git tag requested for verification
+  pub repo_tag: Option<String>,
+  /// Exact git commit hash requested for verification
+  pub repo_commit: Option<String>,
   /// Git commit hash
   pub git_commit: Option<String>,
-  /// TinyGo compiler version
-  pub tinygo_version: String,
-  /// Go version
-  pub go_version: String,
-  /// LLVM version
-  pub llvm_version: String,
+  /// TinyGo compiler version (Go contracts only)
+  pub tinygo_version: Option<String>,
+  /// Go version (Go contracts only)
+  pub go_version: Option<String>,
+  /// LLVM version of the compiler toolchain
+  pub llvm_version: Option<String>,
+  /// Digest of the TinyGo Docker image used for the build (Go contracts only)
+  pub tinygo_img_digest: Option<String>,
+  /// Digest of the toolchain Docker image used for the build (Rust and AssemblyScript contracts only)
+  pub toolchain_img_digest: Option<String>,
+  /// Rust compiler version (Rust contracts only)
+  pub rust_version: Option<String>,
+  /// Cargo profile used for the build (Rust contracts only)
+  pub cargo_profile: Option<String>,
+  /// Cargo features enabled for the build (Rust contracts only)
+  pub cargo_features: Option<Vec<String>>,
+  /// AssemblyScript compiler version (AssemblyScript contracts only)
+  pub asc_version: Option<String>,
+  /// Node.js version used to run the AssemblyScript compiler (AssemblyScript contracts only)
+  pub node_version: Option<String>,
+  /// Entry file of the AssemblyScript compiler, relative to the contract directory (AssemblyScript contracts only)
+  pub asc_entry: Option<String>,
+  /// Additional options passed to the AssemblyScript compiler (AssemblyScript contracts only)
+  pub asc_options: Option<Vec<String>>,
+  /// File name of the uploaded source archive, named after its SHA-256 hash (archive verifications only)
+  pub source_archive: Option<String>,
+  /// Post-processing steps applied in order to the compiled output before stripping
+  pub post_process: Option<Vec<CVPostProcessStep>>,
   /// WASM strip tool that was used on the compiled output
   pub strip_tool: Option<String>,
-  /// Subdirectory within the repository containing the contract source code
+  /// Subdirectory within the repository containing the contract source code (Go package, Cargo crate or AssemblyScript project)
   pub contract_dir: Option<String>,
   /// Subdirectory within the repository containing the Go module (go.mod). Defaults to the repository root if not specified.
   pub go_mod_dir: Option<String>,
@@ -104,23 +415,502 @@ pub struct CVContractResult {
   pub lang: String,
   /// URL of the preserved source code mirror (Gitea)
   pub gitea_url: Option<String>,
+  /// Error of the latest failed mirror attempt
+  pub gitea_error: Option<String>,
+  /// Structural diff of the deployed bytecode against the compiled output when the bytecode does not match
+  #[schema(value_type = Option<Object>)]
+  pub bytecode_diff: Option<WasmDiff>,
+  /// Bytecode CID of the previously verified code if the verification was queued automatically after a contract upgrade
+  pub upgraded_from: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVLogEntry {
+  /// Build step that produced the log line (git, archive, manifest, pull, fetch, compile, postprocess, strip, hash, diff, snapshot, index, sbom, attest, error)
+  pub stage: String,
+  /// Output stream of the log line (stdout, stderr, info)
+  pub stream: String,
+  /// Log message
+  pub msg: String,
+}
+
+/// Live build event streamed over Server-Sent Events
+#[derive(Clone, Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum CVBuildEvent {
+  /// Build state transition (cloning, extracting, pulling, fetching, compiling, postprocessing, stripping, hashing, diffing, mirroring, snapshotting)
+  State {
+    state: String,
+  },
+  /// Build log line, including live container output
+  Log(CVLogEntry),
+  /// Final status of the build attempt, no further events follow
+  Status {
+    status: String,
+  },
+}
+
+impl CVBuildEvent {
+  /// SSE event name
+  pub fn name(&self) -> &'static str {
+    match self {
+      CVBuildEvent::State { .. } => "state",
+      CVBuildEvent::Log(_) => "log",
+      CVBuildEvent::Status { .. } => "status",
+    }
+  }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVBuildLog {
+  #[serde(rename = "_id")]
+  pub code: String,
+  pub contract_id: String,
+  pub request_ts: DateTime,
+  pub truncated: bool,
+  pub logs: Vec<CVLogEntry>,
+}
+
+/// Statement of a successful verification, signed by the server in its canonical form
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVAttestationPayload {
+  /// Attestation format version
+  pub version: u32,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Contract address
+  pub contract_id: String,
+  /// Contract language
+  pub lang: String,
+  /// HTTPS URL of the git repository
+  pub repo_url: Option<String>,
+  /// Git commit hash of the verified source
+  pub git_commit: Option<String>,
+  /// File name of the source archive, named after its SHA-256 hash
+  pub source_archive: Option<String>,
+  pub tinygo_version: Option<String>,
+  pub go_version: Option<String>,
+  pub llvm_version: Option<String>,
+  pub rust_version: Option<String>,
+  pub cargo_profile: Option<String>,
+  pub asc_version: Option<String>,
+  pub node_version: Option<String>,
+  /// Toolchain image used to compile the contract, pinned by digest
+  pub toolchain_image: Option<String>,
+  /// Post-processing steps applied to the compiled output with their pinned versions
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub post_process: Option<Vec<CVPostProcessStep>>,
+  /// Tool used to strip the output WASM file
+  pub strip_tool: Option<String>,
+  /// Verification timestamp in RFC 3339 format
+  pub verified_ts: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVAttestation {
+  #[serde(rename = "_id")]
+  pub code: String,
+  pub contract_id: String,
+  pub payload: String,
+  pub signature: String,
+  pub public_key: String,
+  pub algorithm: String,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVAttestationResult {
+  /// Canonical JSON attestation document exactly as signed
+  pub payload: String,
+  /// Hex encoded signature over the payload
+  pub signature: String,
+  /// Hex encoded public key of the signing key
+  pub public_key: String,
+  /// Signature algorithm
+  pub algorithm: String,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVAttestationKeyResult {
+  /// Hex encoded public key that signs verification attestations
+  pub public_key: String,
+  /// Signature algorithm
+  pub algorithm: String,
+}
+
+/// Compile-only build that reports the resulting CID without matching it against a deployed contract
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVDryRun {
+  #[serde(rename = "_id")]
+  pub id: ObjectId,
+  pub requester: String,
+  pub status: String,
+  pub request_ts: DateTime,
+  pub started_ts: Option<DateTime>,
+  pub completed_ts: Option<DateTime>,
+  /// Build settings, with `code` and `contract_id` set to the dry run ID
+  pub build: CVContract,
+  pub git_commit: Option<String>,
+  pub output_cid: Option<String>,
+  pub exports: Option<Vec<String>>,
+  pub error: Option<String>,
+  pub truncated: bool,
+  pub logs: Vec<CVLogEntry>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVDryRunResult {
+  /// Dry run ID
+  pub id: String,
+  /// Dry run status
+  pub status: String,
+  /// Contract language
+  pub lang: String,
+  /// HTTPS URL of the git repository
+  pub repo_url: String,
+  /// Git commit hash that was compiled
+  pub git_commit: Option<String>,
+  /// Request timestamp
+  pub request_ts: String,
+  /// Timestamp at which the build started
+  pub started_ts: Option<String>,
+  /// Timestamp at which the build finished
+  pub completed_ts: Option<String>,
+  /// Raw CID of the compiled output, which is the contract code CID once deployed
+  pub output_cid: Option<String>,
+  /// Public exports of the compiled output
+  pub exports: Option<Vec<String>>,
+  /// Error of the build if it failed
+  pub error: Option<String>,
+  /// Whether the logs were truncated due to exceeding the size limit
+  pub truncated: bool,
+  /// Log lines of each build step
+  pub logs: Vec<CVLogEntry>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVBuildLogResult {
+  /// Contract address
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Request timestamp of the verification attempt that produced the logs
+  pub request_ts: String,
+  /// Whether the logs were truncated due to exceeding the size limit
+  pub truncated: bool,
+  /// Log lines of each build step
+  pub logs: Vec<CVLogEntry>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct CVQueueItem {
+  /// Contract address
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Language of contract source code
+  pub lang: String,
+  /// Contract verification status
+  pub status: String,
+  /// Contract verification request timestamp
+  pub request_ts: String,
+  /// Position in the queue starting from 1, queued contracts only
+  pub position: Option<usize>,
+  /// Number of build attempts so far
+  pub attempts: i32,
+  /// Timestamp at which the latest build attempt started
+  pub started_ts: Option<String>,
+  /// Earliest timestamp of the next build attempt when retrying
+  pub next_attempt_ts: Option<String>,
+  /// Estimated start timestamp for queued contracts, or estimated completion timestamp for contracts in progress
+  pub eta: Option<String>,
+  /// Completion timestamp of recently completed verifications
+  pub completed_ts: Option<String>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVQueueResult {
+  /// Number of compiler workers
+  pub workers: usize,
+  /// Average build duration in seconds of recently completed verifications
+  pub avg_build_duration: Option<f64>,
+  /// Verifications currently being built
+  pub in_progress: Vec<CVQueueItem>,
+  /// Verifications waiting for a worker, in processing order
+  pub queued: Vec<CVQueueItem>,
+  /// Verifications waiting to be retried after a transient error
+  pub retrying: Vec<CVQueueItem>,
+  /// Recently completed verifications, most recent first
+  pub recent: Vec<CVQueueItem>,
+}
+
+/// Webhook registered by a user, signing every delivery to the user with its secret
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVWebhook {
+  #[serde(rename = "_id")]
+  pub username: String,
+  pub url: Option<String>,
+  pub secret: String,
+  pub created_ts: DateTime,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVWebhookResult {
+  /// URL notified whenever a verification requested by the user finishes
+  pub url: Option<String>,
+  /// HMAC-SHA256 secret used to sign deliveries, only returned upon registration
+  pub secret: Option<String>,
+  /// Registration timestamp
+  pub created_ts: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVDeliveryAttempt {
+  pub ts: DateTime,
+  pub status_code: Option<u16>,
+  pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVDeliveryAttemptResult {
+  /// Attempt timestamp
+  pub ts: String,
+  /// HTTP status code returned by the receiver
+  pub status_code: Option<u16>,
+  /// Error of the attempt if the request failed
+  pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVWebhookDelivery {
+  #[serde(rename = "_id")]
+  pub id: ObjectId,
+  /// Username of the subscriber whose secret signs the payload
+  pub subscriber: String,
+  pub url: String,
+  pub code: String,
+  pub contract_id: String,
+  pub status: String,
+  pub payload: String,
+  pub delivered: bool,
+  pub attempts: Vec<CVDeliveryAttempt>,
+  pub created_ts: DateTime,
+  pub next_attempt_ts: Option<DateTime>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVWebhookDeliveryResult {
+  /// Delivery ID, also sent in the `X-Magi-Delivery` header
+  pub id: String,
+  /// URL the payload is delivered to
+  pub url: String,
+  /// Contract address
+  pub address: String,
+  /// Contract verification status that triggered the delivery
+  pub status: String,
+  /// Whether the payload was delivered successfully
+  pub delivered: bool,
+  /// Delivery attempts so far
+  pub attempts: Vec<CVDeliveryAttemptResult>,
+  /// Timestamp of the next attempt if the payload is yet to be delivered
+  pub next_attempt_ts: Option<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVSourceFile {
+  /// File path relative to the source root
+  pub path: String,
+  /// File size in bytes
+  pub size: u64,
+  /// SHA-256 hash of the file contents
+  pub sha256: String,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVSourceTree {
+  #[serde(rename = "_id")]
+  pub code: String,
+  pub contract_id: String,
+  pub snapshot_ts: DateTime,
+  pub files: Vec<CVSourceFile>,
+  /// SHA-256 hash of the compiled output in the content-addressed store
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub wasm_sha256: Option<String>,
+}
+
+/// Go module dependency of a verified contract
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct CVSbomComponent {
+  /// Module path as required in go.mod
+  pub path: String,
+  /// Required module version
+  pub version: String,
+  /// `h1:` checksum of the module contents from go.sum
+  pub checksum: Option<String>,
+  /// Whether the module is only required indirectly
+  pub indirect: bool,
+  /// Module path or local directory that replaces the module
+  pub replace_path: Option<String>,
+  /// Version of the replacement module
+  pub replace_version: Option<String>,
+}
+
+/// Software bill of materials of a verified contract, recorded from the go.mod and go.sum of the source tree
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVSbom {
+  #[serde(rename = "_id")]
+  pub code: String,
+  pub contract_id: String,
+  /// Path of the main module
+  pub module: String,
+  pub go_version: Option<String>,
+  pub components: Vec<CVSbomComponent>,
+  pub created_ts: DateTime,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVSbomDependentResult {
+  /// Contract address
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Path of the main module of the contract
+  pub module: String,
+  /// Matching dependency
+  pub dependency: CVSbomComponent,
+}
+
+/// Line numbers of a token within a source file
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVSearchFileLines {
+  pub path: String,
+  /// SHA-256 hash of the file contents in the content-addressed store
+  pub sha256: String,
+  pub lines: Vec<u32>,
+}
+
+/// Postings of a token in the verified source tree of a bytecode
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CVSearchPosting {
+  pub token: String,
+  pub code: String,
+  pub contract_id: String,
+  pub files: Vec<CVSearchFileLines>,
+}
+
+#[derive(Clone, Serialize, ToSchema)]
+pub struct CVCodeSearchLine {
+  /// Line number starting from 1
+  pub line: u32,
+  /// Line contents, truncated if too long
+  pub text: String,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVCodeSearchResult {
+  /// Contract address
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// File path relative to the source root
+  pub path: String,
+  /// Lines containing every search term
+  pub lines: Vec<CVCodeSearchLine>,
+}
+
+/// `metadata.json` of a verification bundle, describing how to reproduce the compiled output from the bundled source tree
+#[derive(Clone, Serialize)]
+pub struct CVBundleMetadata {
+  pub address: String,
+  pub code: String,
+  pub lang: String,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_url: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_branch: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub repo_tag: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub git_commit: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub source_archive: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub go_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub llvm_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub tinygo_img_digest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub toolchain_img_digest: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub rust_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_profile: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub cargo_features: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub node_version: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_entry: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub asc_options: Option<Vec<String>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub contract_dir: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub go_mod_dir: Option<String>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub post_process: Option<Vec<CVPostProcessStep>>,
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub strip_tool: Option<String>,
+  pub exports: Option<Vec<String>>,
+  pub license: Option<String>,
+  pub verified_ts: Option<String>,
+  /// Files of the source tree under `source/`
+  pub files: Vec<CVSourceFile>,
+  /// SHA-256 hash of `build.wasm`, absent from bundles of snapshots that predate stored outputs
+  pub wasm_sha256: Option<String>,
+}
+
+#[derive(Clone, Serialize, ToResponse, ToSchema)]
+pub struct CVSourceTreeResult {
+  /// Contract address
+  pub address: String,
+  /// Contract bytecode CID
+  pub code: String,
+  /// Timestamp of the source snapshot
+  pub snapshot_ts: String,
+  /// Files of the verified source tree
+  pub files: Vec<CVSourceFile>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct GithubLicense {
+  pub spdx_id: Option<String>,
 }
 
 #[derive(Clone, Deserialize)]
 pub struct GithubRepoInfo {
-  pub default_branch: String,
   pub size: usize,
-  pub license: Option<String>,
+  pub license: Option<GithubLicense>,
+}
+
+#[derive(Clone, Deserialize)]
+pub struct GitlabLicense {
+  pub key: String,
 }
 
 #[derive(Clone, Deserialize)]
-pub struct GithubBranchCommit {
-  pub sha: String,
+pub struct GitlabProjectInfo {
+  pub license: Option<GitlabLicense>,
 }
 
 #[derive(Clone, Deserialize)]
-pub struct GithubBranchInfo {
-  pub commit: GithubBranchCommit,
+pub struct GiteaRepoInfo {
+  pub size: usize,
+  pub licenses: Option<Vec<String>>,
 }
 
 lazy_static! {
@@ -152,3 +942,53 @@ lazy_static! {
     ),
   ]);
 }
+
+lazy_static! {
+  /// https://hub.docker.com/_/rust/tags
+  pub static ref rust_versions: HashMap<String, CVRustLibVersions> = HashMap::from([
+    (
+      String::from("1.85.1"),
+      CVRustLibVersions {
+        llvm: String::from("19.1.7"),
+        image: String::from("rust:1.85.1-slim-bookworm"),
+        img_digest: String::new(),
+      },
+    ),
+    (
+      String::from("1.88.0"),
+      CVRustLibVersions {
+        llvm: String::from("20.1.5"),
+        image: String::from("rust:1.88.0-slim-bookworm"),
+        img_digest: String::new(),
+      },
+    ),
+    (
+      String::from("1.90.0"),
+      CVRustLibVersions {
+        llvm: String::from("20.1.8"),
+        image: String::from("rust:1.90.0-slim-bookworm"),
+        img_digest: String::new(),
+      },
+    ),
+  ]);
+
+  /// https://www.npmjs.com/package/assemblyscript?activeTab=versions
+  pub static ref asc_versions: HashMap<String, CVAscLibVersions> = HashMap::from([
+    (
+      String::from("0.27.31"),
+      CVAscLibVersions {
+        node: String::from("22.14.0"),
+        image: String::from("node:22.14.0-bookworm-slim"),
+        img_digest: String::new(),
+      },
+    ),
+    (
+      String::from("0.28.2"),
+      CVAscLibVersions {
+        node: String::from("22.14.0"),
+        image: String::from("node:22.14.0-bookworm-slim"),
+        img_digest: String::new(),
+      },
+    ),
+  ]);
+}
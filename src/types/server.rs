@@ -25,14 +25,38 @@ pub enum RespErr {
   #[display("Contract verifier is disabled")] CvDisabled,
   #[display("Only contract deployer or owner can request verification")] CvNotAuthorized,
   #[display("Only whitelisted users can request verification")] CvNotWhitelisted,
-  #[display("Invalid GitHub repository URL")] CvInvalidGitHubURL,
-  #[display("Invalid git branch name")] CvInvalidGitBranch,
+  #[display("Invalid git repository URL")] CvInvalidGitURL,
+  #[display("Invalid git revision: {msg}")] CvInvalidGitRef {
+    msg: String,
+  },
   #[display("Invalid Wasm strip tool name")] CvInvalidWasmStripTool,
+  #[display("Invalid post-processing step: {msg}")] CvInvalidPostProcess {
+    msg: String,
+  },
   #[display("Invalid TinyGo version")] CvInvalidTinyGoVersion,
   #[display("Invalid contract directory path")] CvInvalidContractDir,
   #[display("Invalid Go module directory path")] CvInvalidGoModDir,
+  #[display("Invalid Rust version")] CvInvalidRustVersion,
+  #[display("Invalid Cargo profile name")] CvInvalidCargoProfile,
+  #[display("Invalid Cargo feature name")] CvInvalidCargoFeature,
+  #[display("Invalid AssemblyScript compiler version")] CvInvalidAscVersion,
+  #[display("Invalid or disallowed AssemblyScript compiler option")] CvInvalidAscOption,
   #[display("Verification retry is only allowed 12 hours after the previous request time")] CvRetryLater,
   #[display("A similar contract was already verified")] CvSimilarMatch,
+  #[display("Build logs not found")] CvLogsNotFound,
+  #[display("Verified source files not found")] CvSourceNotFound,
+  #[display("Source file not found")] CvSourceFileNotFound,
+  #[display("Source archive is too large")] CvArchiveTooLarge,
+  #[display("Invalid callback URL")] CvInvalidCallbackURL,
+  #[display("Register a webhook before requesting verification callbacks")] CvWebhookNotRegistered,
+  #[display("Webhook not found")] CvWebhookNotFound,
+  #[display("Dry run not found")] CvDryRunNotFound,
+  #[display("Verification attestation not found")] CvAttestationNotFound,
+  #[display("Dependency SBOM not found")] CvSbomNotFound,
+  #[display("Only admins can perform this action")] CvAdminOnly,
+  #[display("Invalid source archive: {msg}")] CvInvalidArchive {
+    msg: String,
+  },
   #[display("{msg}")] InternalErr {
     msg: String,
   },
@@ -77,14 +101,32 @@ impl actix_web::error::ResponseError for RespErr {
       RespErr::CvDisabled => StatusCode::IM_A_TEAPOT,
       RespErr::CvNotAuthorized => StatusCode::FORBIDDEN,
       RespErr::CvNotWhitelisted => StatusCode::FORBIDDEN,
-      RespErr::CvInvalidGitHubURL => StatusCode::BAD_REQUEST,
-      RespErr::CvInvalidGitBranch => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidGitURL => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidGitRef { .. } => StatusCode::BAD_REQUEST,
       RespErr::CvInvalidWasmStripTool => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidPostProcess { .. } => StatusCode::BAD_REQUEST,
       RespErr::CvInvalidTinyGoVersion => StatusCode::BAD_REQUEST,
       RespErr::CvInvalidContractDir => StatusCode::BAD_REQUEST,
       RespErr::CvInvalidGoModDir => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidRustVersion => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidCargoProfile => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidCargoFeature => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidAscVersion => StatusCode::BAD_REQUEST,
+      RespErr::CvInvalidAscOption => StatusCode::BAD_REQUEST,
       RespErr::CvRetryLater => StatusCode::TOO_MANY_REQUESTS,
       RespErr::CvSimilarMatch => StatusCode::FOUND,
+      RespErr::CvLogsNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvSourceNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvSourceFileNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvArchiveTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+      RespErr::CvInvalidCallbackURL => StatusCode::BAD_REQUEST,
+      RespErr::CvWebhookNotRegistered => StatusCode::BAD_REQUEST,
+      RespErr::CvWebhookNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvDryRunNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvAttestationNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvSbomNotFound => StatusCode::NOT_FOUND,
+      RespErr::CvAdminOnly => StatusCode::FORBIDDEN,
+      RespErr::CvInvalidArchive { .. } => StatusCode::BAD_REQUEST,
     }
   }
 }
@@ -1,9 +1,11 @@
+use async_trait::async_trait;
+use bollard::{ container::Config, models::HostConfig };
 use serde::Deserialize;
 use serde_json::{ self, json };
 use actix_web::{ middleware::NormalizePath, test, web, App };
-use std::{ collections::HashSet, env, time::{ Instant, Duration } };
+use std::{ collections::HashSet, env, fs, path::Path, sync::Arc, time::{ Instant, Duration } };
 use magi_bb::{
-  compiler::Compiler,
+  compiler::{ runner::BuildRunner, BuildError, BuildLog, Compiler },
   config::{ CompilerConf, DbConf, GoCompilerConf },
   endpoints::cv_api,
   mongo::MongoDB,
@@ -37,14 +39,58 @@ struct CVResp {
   pub lang: Option<String>,
 }
 
-async fn setup_db() -> MongoDB {
+/// In-process runner that never starts a container. The compile phase writes the given WASM file as `build.wasm`
+/// into the directory bound to `/out`, allowing the verification pipeline to run without a container runtime.
+#[derive(Clone)]
+struct FakeRunner {
+  wasm: Vec<u8>,
+}
+
+impl FakeRunner {
+  fn new(wasm: Vec<u8>) -> Self {
+    FakeRunner { wasm }
+  }
+}
+
+#[async_trait]
+impl BuildRunner for FakeRunner {
+  async fn pull_image(&self, _repo: &str, _digest: &str, _log: &BuildLog) -> Result<(), BuildError> {
+    Ok(())
+  }
+
+  async fn run_container(
+    &self,
+    _name: &str,
+    cont_conf: Config<String>,
+    stage: &str,
+    _commit_as: Option<&str>,
+    log: &BuildLog
+  ) -> Result<i64, BuildError> {
+    let out_dir = cont_conf.host_config
+      .and_then(|h| h.binds)
+      .unwrap_or_default()
+      .iter()
+      .find_map(|b| b.strip_suffix(":/out").map(String::from));
+    if let Some(out_dir) = out_dir {
+      fs::write(Path::new(&out_dir).join("build.wasm"), &self.wasm).map_err(|e|
+        BuildError::Failed(format!("Failed to write build.wasm: {}", e))
+      )?;
+      log.push(stage, "stdout", "Wrote build.wasm");
+    }
+    Ok(0)
+  }
+
+  async fn remove_image(&self, _image: &str) {}
+}
+
+async fn setup_db(name: &str) -> MongoDB {
   // connect and drop existing db
   let db = MongoDB::init(
     &(DbConf {
       mongo_url: format!("mongodb://127.0.0.1:27017"),
-      magi_db_name: format!("mbb-e2e"),
-      be_db_name: format!("mbb-e2e-be"),
-      cv_db_name: format!("mbb-e2e-cv"),
+      magi_db_name: format!("{}", name),
+      be_db_name: format!("{}-be", name),
+      cv_db_name: format!("{}-cv", name),
     })
   ).await.expect("failed to setup mongodb database");
   db.contracts.drop().await.expect("failed to drop contracts collection");
@@ -63,40 +109,67 @@ async fn setup_db() -> MongoDB {
   return db;
 }
 
-#[actix_web::test]
-async fn test_e2e_verify_contract_go() {
-  let db = setup_db().await;
-  let http_client = reqwest::Client::new();
+fn go_compiler_conf(suffix: &str) -> GoCompilerConf {
   let src_dir = env
     ::var("VSC_CV_TEST_SRC_DIR")
     .unwrap_or_else(|_| String::from("/Users/techcoderx/vsc-blocks-backend/go_compiler"));
   let out_dir = env
     ::var("VSC_CV_TEST_OUT_DIR")
     .unwrap_or_else(|_| String::from("/Users/techcoderx/vsc-blocks-backend/artifacts"));
-  let compiler = Compiler::init(
-    &db,
-    &http_client,
-    &(GoCompilerConf {
-      src_dir: src_dir,
-      src_host_dir: None,
-      output_dir: out_dir,
-      output_host_dir: None,
-      timeout: 20,
-    }),
-    &(CompilerConf {
-      enabled: Some(true),
-      github_api_key: env
-        ::var("VSC_CV_TEST_GITHUB_KEY")
-        .map(|k| Some(k))
-        .unwrap_or(None),
-      wasm_strip: format!("/usr/local/bin/wasm-strip"),
-      wasm_tools: format!("/usr/local/bin/wasm-tools"),
-      whitelist: Vec::new(),
-      fix_permissions: Some(false),
-      max_repo_size: Some(102400),
-    }),
-    None
-  );
+  GoCompilerConf {
+    src_dir: format!("{}{}", src_dir, suffix),
+    src_host_dir: None,
+    output_dir: format!("{}{}", out_dir, suffix),
+    output_host_dir: None,
+    deps_dir: None,
+    deps_host_dir: None,
+    timeout: 20,
+    goproxy: None,
+    fetch_network: None,
+    memory: None,
+    cpus: None,
+    pids_limit: None,
+  }
+}
+
+fn compiler_conf() -> CompilerConf {
+  CompilerConf {
+    enabled: Some(true),
+    github_api_key: env
+      ::var("VSC_CV_TEST_GITHUB_KEY")
+      .map(|k| Some(k))
+      .unwrap_or(None),
+    wasm_strip: format!("/usr/local/bin/wasm-strip"),
+    wasm_tools: format!("/usr/local/bin/wasm-tools"),
+    whitelist: Vec::new(),
+    admins: None,
+    fix_permissions: Some(false),
+    runtime: None,
+    podman_socket: None,
+    max_repo_size: Some(102400),
+    workers: Some(2),
+    max_attempts: None,
+    retry_backoff: None,
+    webhook_max_attempts: None,
+    max_log_size: Some(1048576),
+    archive_dir: None,
+    max_archive_size: None,
+    allowed_git_hosts: None,
+    git_hosts: None,
+    ipfs_gateway: None,
+    snapshot_dir: None,
+    attestation_key: None,
+    tinygo_versions: None,
+    post_process_tools: None,
+    upgrade_watch_interval: None,
+  }
+}
+
+#[actix_web::test]
+async fn test_e2e_verify_contract_go() {
+  let db = setup_db("mbb-e2e").await;
+  let http_client = reqwest::Client::new();
+  let compiler = Compiler::init(&db, &http_client, &go_compiler_conf(""), &compiler_conf(), None);
   let server_ctx = Context { db: db, compiler: Some(compiler), http_client: http_client.clone() };
   let app = test::init_service(
     App::new()
@@ -136,7 +209,8 @@ async fn test_e2e_verify_contract_go() {
   let resp: CVResp = test::call_and_read_body_json(&app, req_get_inserted_contract).await;
   assert_eq!(resp.error, None);
   assert_eq!(&resp.address.unwrap(), contract_id);
-  assert_eq!(&resp.status.unwrap(), "queued");
+  let status = resp.status.unwrap();
+  assert!(status == "queued" || status == "in progress");
   assert_eq!(&resp.lang.unwrap(), "go");
   assert_eq!(&resp.tinygo_version.unwrap(), "0.38.0");
   assert_eq!(&resp.repo_name.unwrap(), "techcoderx/go-contract-template");
@@ -206,7 +280,8 @@ async fn test_e2e_verify_contract_go() {
   let resp: CVResp = test::call_and_read_body_json(&app, req_get_inserted_contract).await;
   assert_eq!(resp.error, None);
   assert_eq!(&resp.address.unwrap(), contract_id);
-  assert_eq!(&resp.status.unwrap(), "queued");
+  let status = resp.status.unwrap();
+  assert!(status == "queued" || status == "in progress");
   assert_eq!(&resp.lang.unwrap(), "go");
   assert_eq!(&resp.tinygo_version.unwrap(), "0.38.0");
   assert_eq!(&resp.repo_name.unwrap(), "techcoderx/go-contract-template");
@@ -249,3 +324,69 @@ async fn test_e2e_verify_contract_go() {
     tokio::time::sleep(poll_interval).await;
   }
 }
+
+#[actix_web::test]
+async fn test_fake_runner_writes_output() {
+  let out = env::temp_dir().join(format!("magi-bb-runner-test-{}", std::process::id()));
+  fs::create_dir_all(&out).unwrap();
+  let runner = FakeRunner::new(vec![0, 97, 115, 109, 1, 0, 0, 0]);
+  let log = BuildLog::new(1024);
+  let conf = |binds: Vec<String>| Config { host_config: Some(HostConfig { binds: Some(binds), ..Default::default() }), ..Default::default() };
+  let fetch = runner.run_container("test", conf(vec![String::from("/src:/src")]), "fetch", Some("deps"), &log).await;
+  assert!(matches!(fetch, Ok(0)));
+  assert!(!out.join("build.wasm").exists());
+  let out_bind = format!("{}:/out", out.to_str().unwrap());
+  let compile = runner.run_container("test", conf(vec![String::from("/src:/src"), out_bind]), "compile", None, &log).await;
+  assert!(matches!(compile, Ok(0)));
+  assert_eq!(fs::read(out.join("build.wasm")).unwrap(), vec![0, 97, 115, 109, 1, 0, 0, 0]);
+  fs::remove_dir_all(&out).unwrap();
+}
+
+#[actix_web::test]
+async fn test_e2e_verify_contract_fake_runner() {
+  let db = setup_db("mbb-e2e-fake").await;
+  let http_client = reqwest::Client::new();
+  // a minimal module with no exports, which cannot match the deployed hello world contract
+  let runner = Arc::new(FakeRunner::new(vec![0, 97, 115, 109, 1, 0, 0, 0]));
+  let compiler = Compiler::with_runner(&db, &http_client, &go_compiler_conf("-fake"), &compiler_conf(), None, runner);
+  let server_ctx = Context { db: db, compiler: Some(compiler), http_client: http_client.clone() };
+  let app = test::init_service(
+    App::new()
+      .wrap(NormalizePath::trim())
+      .app_data(web::Data::new(server_ctx.clone()))
+      .service(web::scope("/cv-api/v1").service(cv_api::verify_new).service(cv_api::contract_info))
+  ).await;
+
+  let contract_id = "vsc1Bem8RnoLgGPP7E2MBN52ekrdVqy2LNpSqF";
+  let req_verify_new = test::TestRequest
+    ::post()
+    .set_json(
+      json!({
+        "repo_url": "https://github.com/techcoderx/go-contract-template",
+        "repo_branch": "wip2",
+        "tinygo_version": "0.38.0",
+      })
+    )
+    .uri(format!("/cv-api/v1/verify/{}/new", contract_id).as_str())
+    .to_request();
+  let resp: SuccessResp = test::call_and_read_body_json(&app, req_verify_new).await;
+  assert_eq!(resp.error, None);
+  assert_eq!(resp.success.unwrap(), true);
+
+  // Poll contract status until the compiled output is compared against the deployed bytecode
+  let start_time = Instant::now();
+  let timeout = Duration::from_secs(30);
+  loop {
+    let req_status = test::TestRequest::get().uri(format!("/cv-api/v1/contract/{}", contract_id).as_str()).to_request();
+    let resp: CVResp = test::call_and_read_body_json(&app, req_status).await;
+    let current_status = resp.status.unwrap();
+    if current_status == "not match" {
+      break;
+    }
+    assert_ne!(current_status, "failed", "Contract verification failed");
+    if start_time.elapsed() > timeout {
+      panic!("Timeout waiting for verification to complete");
+    }
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+  }
+}